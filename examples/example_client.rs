@@ -1,12 +1,38 @@
 use {
-    srpc::{client::Client, transport::Transport},
+    srpc::{
+        client::Client,
+        json_rpc::{ApplicationError, Error, ErrorKind},
+        transport::Transport,
+    },
     std::sync::Arc,
 };
 
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+struct NotFound {
+    elem: String,
+}
+
+impl ApplicationError for NotFound {
+    fn into_error(self) -> Error {
+        Error::custom(1, "Element not found", serde_json::to_value(self).ok())
+    }
+
+    fn from_error(error: Error) -> Result<Self, Error> {
+        match (error.kind, &error.data) {
+            (ErrorKind::Custom(1), Some(data)) => {
+                serde_json::from_value(data.clone()).map_err(|_| error)
+            }
+            _ => Err(error),
+        }
+    }
+}
+
 #[srpc::client]
 trait StrService {
     async fn contains(data: String, elem: String) -> bool;
 
+    async fn find(data: String, elem: String) -> Result<usize, NotFound>;
+
     #[notification]
     async fn set_data(is_cool: bool);
 }
@@ -16,14 +42,25 @@ async fn main() {
     env_logger::init();
     let transporter = Arc::new(Transport::new());
     let client = Client::new(([127, 0, 0, 1], 8080).into(), transporter.clone());
+    let service = StrServiceClient::new(client);
 
     for i in 0..100 {
-        let _ = StrService::set_data(&client, i % 2 == 0).await;
+        let _ = service.set_data(i % 2 == 0).await;
         println!(
             "{}",
-            StrService::contains(&client, String::from("cool lib"), String::from("lib"))
+            service
+                .contains(String::from("cool lib"), String::from("lib"))
                 .await
                 .unwrap()
         );
     }
+
+    match service
+        .find(String::from("cool lib"), String::from("rpc"))
+        .await
+        .unwrap()
+    {
+        Ok(pos) => println!("Found at {}", pos),
+        Err(NotFound { elem }) => println!("'{}' is not found", elem),
+    }
 }
@@ -1,16 +1,40 @@
+use srpc::json_rpc::{ApplicationError, Error, ErrorKind};
 use srpc::server::Context;
-use srpc::server::Server;
+use srpc::server::{ExecutionMode, Server};
 use std::sync::Arc;
 
 struct StrService;
 
+#[derive(serde::Serialize, serde::Deserialize)]
+struct NotFound {
+    elem: String,
+}
+
+impl ApplicationError for NotFound {
+    fn into_error(self) -> Error {
+        Error::custom(1, "Element not found", serde_json::to_value(self).ok())
+    }
+
+    fn from_error(error: Error) -> Result<Self, Error> {
+        match (error.kind, &error.data) {
+            (ErrorKind::Custom(1), Some(data)) => {
+                serde_json::from_value(data.clone()).map_err(|_| error)
+            }
+            _ => Err(error),
+        }
+    }
+}
+
 #[srpc::service]
-#[allow(unused)]
 impl StrService {
     async fn contains(data: String, elem: String) -> bool {
         data.contains(&elem)
     }
 
+    async fn find(data: String, elem: String) -> Result<usize, NotFound> {
+        data.find(&elem).ok_or(NotFound { elem })
+    }
+
     async fn set_data(context: Arc<Context>, is_cool: bool) {
         println!("Socket {:?}", context.caller_addr);
         println!("Set a cool variable to: {}", is_cool);
@@ -20,6 +44,8 @@ impl StrService {
 #[tokio::main]
 async fn main() {
     env_logger::init();
-    let server = Server::new(StrService, StrService::caller);
+    let mut server = Server::new(StrService, StrService::caller);
+    // 'set_data' notifications are applied in the order they are sent.
+    server.set_execution_mode(ExecutionMode::SequentialNotifications);
     let _ = server.serve("127.0.0.1:8080").await;
 }
@@ -1,6 +1,7 @@
 //use srpc::Server;
 
 #[srpc_macros::client]
+#[allow(dead_code)]
 trait StrService {
     fn foo(data: i32) -> i32;
 }
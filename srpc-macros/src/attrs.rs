@@ -0,0 +1,219 @@
+//! srpc attributes of RPC methods and arguments of the macros.
+
+use {
+    crate::utils::collect_results,
+    proc_macro2::TokenStream,
+    std::collections::HashSet,
+    syn::{parse::Parser, punctuated::Punctuated},
+};
+
+/// srpc attributes of an RPC method. They are not real attributes, so they are removed from
+/// the method when parsed.
+#[derive(Default)]
+pub(crate) struct MethodAttrs {
+    /// '#[notification]': The client does not wait for a response.
+    pub notification: Option<syn::Attribute>,
+    /// '#[blocking]': The method is run on the blocking thread pool.
+    pub blocking: Option<syn::Attribute>,
+    /// '#[detached]': The method runs to the end even if the call is cancelled or the
+    /// connection is closed.
+    pub detached: Option<syn::Attribute>,
+    /// '#[subscription]': The method returns a stream of items which are sent as notifications.
+    pub subscription: Option<syn::Attribute>,
+    /// '#[rpc(..)]': Renames the method on the wire.
+    pub rpc: Option<syn::Attribute>,
+    /// 'name = ".."' of '#[rpc(..)]'
+    pub name: Option<syn::LitStr>,
+    /// 'alias = ".."' of '#[rpc(..)]', can be given more than once. Aliases are complete
+    /// method names, the namespace is not prepended to them.
+    pub aliases: Vec<syn::LitStr>,
+}
+
+impl MethodAttrs {
+    /// Parses and removes the srpc attributes from the given attributes.
+    pub fn take(attrs: &mut Vec<syn::Attribute>) -> syn::Result<Self> {
+        let mut method_attrs = Self::default();
+        let mut result: syn::Result<()> = Ok(());
+        attrs.retain(|attr| {
+            if attr.path.is_ident("rpc") {
+                if let Err(err) = method_attrs.parse_rpc(attr) {
+                    match result.as_mut() {
+                        Err(error) => error.combine(err),
+                        Ok(_) => result = Err(err),
+                    }
+                }
+                method_attrs.rpc = Some(attr.clone());
+                return false;
+            }
+
+            let (name, slot) = if attr.path.is_ident("notification") {
+                ("notification", &mut method_attrs.notification)
+            } else if attr.path.is_ident("blocking") {
+                ("blocking", &mut method_attrs.blocking)
+            } else if attr.path.is_ident("detached") {
+                ("detached", &mut method_attrs.detached)
+            } else if attr.path.is_ident("subscription") {
+                ("subscription", &mut method_attrs.subscription)
+            } else {
+                return true;
+            };
+
+            if !attr.tokens.is_empty() {
+                let err = syn::Error::new_spanned(
+                    &attr.tokens,
+                    format!("#[{}] does not take any arguments", name),
+                );
+                match result.as_mut() {
+                    Err(error) => error.combine(err),
+                    Ok(_) => result = Err(err),
+                }
+            }
+            *slot = Some(attr.clone());
+            false
+        });
+        result.map(|_| method_attrs)
+    }
+
+    fn parse_rpc(&mut self, attr: &syn::Attribute) -> syn::Result<()> {
+        let args = match attr.parse_meta()? {
+            syn::Meta::List(list) => list.nested,
+            meta => {
+                return Err(syn::Error::new_spanned(
+                    meta,
+                    "Expected '#[rpc(name = \"..\", alias = \"..\")]'",
+                ))
+            }
+        };
+        collect_results(args.iter().map(|arg| match arg {
+            syn::NestedMeta::Meta(syn::Meta::NameValue(meta)) if meta.path.is_ident("name") => {
+                self.name = Some(lit_str(arg)?);
+                Ok(())
+            }
+            syn::NestedMeta::Meta(syn::Meta::NameValue(meta)) if meta.path.is_ident("alias") => {
+                self.aliases.push(lit_str(arg)?);
+                Ok(())
+            }
+            _ => Err(syn::Error::new_spanned(
+                arg,
+                "Unknown argument, #[rpc] takes 'name = \"..\"' and 'alias = \"..\"'",
+            )),
+        }))
+        .map(|_| ())
+    }
+
+    /// Fails if the attribute is set, for the macros that have no use for it.
+    pub fn reject(attr: &Option<syn::Attribute>, message: &str) -> syn::Result<()> {
+        match attr {
+            Some(attr) => Err(syn::Error::new_spanned(attr, message)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Arguments of the srpc macros themselves, e.g. '#[srpc::service(namespace = "str")]'.
+pub(crate) struct MacroArgs {
+    /// Prefix of every method name on the wire.
+    pub namespace: Option<syn::LitStr>,
+    /// Put between the namespace and the method name. Defaults to '_'.
+    pub namespace_separator: Option<syn::LitStr>,
+    /// 'openrpc': The service is described by an OpenRPC document.
+    pub openrpc: Option<syn::Path>,
+}
+
+impl MacroArgs {
+    pub fn parse(macro_name: &str, args: TokenStream) -> syn::Result<Self> {
+        let mut macro_args = Self {
+            namespace: None,
+            namespace_separator: None,
+            openrpc: None,
+        };
+        let args = Punctuated::<syn::NestedMeta, syn::Token![,]>::parse_terminated.parse2(args)?;
+        collect_results(args.iter().map(|arg| {
+            let slot = match arg {
+                syn::NestedMeta::Meta(syn::Meta::Path(path)) if path.is_ident("openrpc") => {
+                    macro_args.openrpc = Some(path.clone());
+                    return Ok(());
+                }
+                syn::NestedMeta::Meta(syn::Meta::NameValue(arg))
+                    if arg.path.is_ident("namespace") =>
+                {
+                    &mut macro_args.namespace
+                }
+                syn::NestedMeta::Meta(syn::Meta::NameValue(arg))
+                    if arg.path.is_ident("namespace_separator") =>
+                {
+                    &mut macro_args.namespace_separator
+                }
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        arg,
+                        format!(
+                            "Unknown argument, #[{}] takes 'namespace = \"..\"', \
+                             'namespace_separator = \"..\"' and 'openrpc'",
+                            macro_name
+                        ),
+                    ))
+                }
+            };
+            *slot = Some(lit_str(arg)?);
+            Ok(())
+        }))?;
+        Ok(macro_args)
+    }
+
+    /// Returns the name of the method on the wire.
+    pub fn method_name(&self, ident: &syn::Ident, attrs: &MethodAttrs) -> String {
+        let name = match &attrs.name {
+            Some(name) => name.value(),
+            None => ident.to_string(),
+        };
+        match &self.namespace {
+            Some(namespace) => {
+                let separator = match &self.namespace_separator {
+                    Some(separator) => separator.value(),
+                    None => String::from("_"),
+                };
+                format!("{}{}{}", namespace.value(), separator, name)
+            }
+            None => name,
+        }
+    }
+}
+
+/// Checks that every method is reachable by a distinct name which is not reserved by JSON-RPC.
+pub(crate) fn check_method_names<'a>(
+    names: impl Iterator<Item = (&'a syn::Ident, String)>,
+) -> syn::Result<()> {
+    let mut seen = HashSet::new();
+    collect_results(names.map(|(ident, name)| {
+        if name.starts_with("rpc.") {
+            Err(syn::Error::new_spanned(
+                ident,
+                format!("Method name '{}' is reserved, names starting with 'rpc.' are for JSON-RPC itself", name),
+            ))
+        } else if !seen.insert(name.clone()) {
+            Err(syn::Error::new_spanned(
+                ident,
+                format!("Method name '{}' is used more than once", name),
+            ))
+        } else {
+            Ok(())
+        }
+    }))
+    .map(|_| ())
+}
+
+/// Returns the string value of a 'key = "value"' argument.
+fn lit_str(arg: &syn::NestedMeta) -> syn::Result<syn::LitStr> {
+    match arg {
+        syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue {
+            lit: syn::Lit::Str(lit),
+            ..
+        })) => Ok(lit.clone()),
+        syn::NestedMeta::Meta(syn::Meta::NameValue(arg)) => Err(syn::Error::new_spanned(
+            &arg.lit,
+            "Expected a string literal",
+        )),
+        _ => Err(syn::Error::new_spanned(arg, "Expected 'key = \"value\"'")),
+    }
+}
@@ -0,0 +1,300 @@
+//! Code generation for the client side.
+
+use {
+    crate::{
+        attrs::{MacroArgs, MethodAttrs},
+        utils::{collect_results, is_result, param_ident, return_type, stream_item_type},
+    },
+    proc_macro2::TokenStream,
+    quote::{format_ident, quote},
+};
+
+pub(crate) fn expand(attrs: TokenStream, mut input: syn::ItemTrait) -> syn::Result<TokenStream> {
+    let args = MacroArgs::parse("srpc::client", attrs)?;
+    if let Some(openrpc) = &args.openrpc {
+        return Err(syn::Error::new_spanned(
+            openrpc,
+            "'openrpc' can only be used in an srpc service or interface",
+        ));
+    }
+
+    let vis = input.vis.clone();
+    let self_ident = input.ident.clone();
+    let client_ident = format_ident!("{}Client", self_ident);
+    let (signatures, methods): (Vec<_>, Vec<_>) =
+        collect_results(input.items.iter_mut().map(|item| {
+            if let syn::TraitItem::Method(item_method) = item {
+                let attrs = MethodAttrs::take(&mut item_method.attrs)?;
+                MethodAttrs::reject(
+                    &attrs.blocking,
+                    "#[blocking] can only be used in an srpc service",
+                )?;
+                MethodAttrs::reject(
+                    &attrs.detached,
+                    "#[detached] can only be used in an srpc service",
+                )?;
+                // The client only ever calls the method by its name.
+                if let Some(alias) = attrs.aliases.first() {
+                    return Err(syn::Error::new_spanned(
+                        alias,
+                        "Aliases can only be used in an srpc service",
+                    ));
+                }
+
+                // Stub methods always take '&self', so it is optional in the declaration.
+                let params =
+                    collect_results(item_method.sig.inputs.iter().filter_map(
+                        |param| match param {
+                            syn::FnArg::Typed(param) => Some(param_ident(param).map(|_| param)),
+                            syn::FnArg::Receiver(receiver)
+                                if receiver.reference.is_some()
+                                    && receiver.mutability.is_none() =>
+                            {
+                                None
+                            }
+                            syn::FnArg::Receiver(receiver) => Some(Err(syn::Error::new_spanned(
+                                receiver,
+                                "'self' can only be used in format '&self' in an RPC client",
+                            ))),
+                        },
+                    ))?;
+
+                let method = ClientMethod::new(
+                    &item_method.sig.ident,
+                    params,
+                    return_type(&item_method.sig.output),
+                    &attrs,
+                    &args,
+                )?;
+                let method_attrs = &item_method.attrs;
+                let signature = method.signature();
+                Ok((
+                    quote! {
+                        #(#method_attrs)*
+                        #signature;
+                    },
+                    method.expand(None),
+                ))
+            } else {
+                Err(syn::Error::new_spanned(
+                    item,
+                    "Only methods are allowed in an srpc client.",
+                ))
+            }
+        }))?
+        .into_iter()
+        .unzip();
+
+    // The trait is kept as the interface of the stub, so test doubles can implement it too.
+    input.items = collect_results(signatures.into_iter().map(syn::parse2))?;
+    let stub = client_stub(&vis, &client_ident);
+
+    Ok(quote! {
+        #input
+
+        #stub
+
+        impl #self_ident for #client_ident {
+            #(#methods)*
+        }
+    })
+}
+
+/// Generates the client stub struct which wraps an 'srpc::client::Client'.
+pub(crate) fn client_stub(vis: &syn::Visibility, client_ident: &syn::Ident) -> TokenStream {
+    quote! {
+        #[derive(Clone)]
+        #vis struct #client_ident {
+            client: srpc::client::Client,
+        }
+
+        impl #client_ident {
+            #vis fn new(client: srpc::client::Client) -> Self {
+                Self { client }
+            }
+
+            /// Returns the underlying client.
+            #vis fn client(&self) -> &srpc::client::Client {
+                &self.client
+            }
+        }
+    }
+}
+
+/// An RPC method of a generated client.
+pub(crate) struct ClientMethod<'a> {
+    ident: &'a syn::Ident,
+    /// Name of the method on the wire
+    name: String,
+    params: Vec<&'a syn::PatType>,
+    return_type: Option<&'a syn::Type>,
+    is_notif: bool,
+    /// 'T' of 'impl Stream<Item = T>' if the method is a subscription
+    subscription_item: Option<&'a syn::Type>,
+}
+
+impl<'a> ClientMethod<'a> {
+    pub fn new(
+        ident: &'a syn::Ident,
+        params: Vec<&'a syn::PatType>,
+        return_type: Option<&'a syn::Type>,
+        attrs: &MethodAttrs,
+        args: &MacroArgs,
+    ) -> syn::Result<Self> {
+        let is_notif = attrs.notification.is_some();
+        if let (true, Some(return_type)) = (is_notif, return_type) {
+            return Err(syn::Error::new_spanned(
+                return_type,
+                "Notification functions should return ()",
+            ));
+        }
+
+        let subscription_item = match &attrs.subscription {
+            Some(attr) => match return_type.and_then(stream_item_type) {
+                Some(item) if !is_notif => Some(item),
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        attr,
+                        "#[subscription] methods should return 'impl Stream<Item = T>'",
+                    ))
+                }
+            },
+            None => None,
+        };
+
+        Ok(Self {
+            ident,
+            name: args.method_name(ident, attrs),
+            params,
+            return_type,
+            is_notif,
+            subscription_item,
+        })
+    }
+
+    /// Generates the signature of the method as it is declared in a trait.
+    pub fn signature(&self) -> TokenStream {
+        let method_ident = self.ident;
+        let params = self.signature_params();
+        let ret_type = match (self.subscription_item, self.return_type) {
+            (Some(item), _) => quote! { srpc::client::Subscription<#item> },
+            (None, Some(ret_type)) => quote! { #ret_type },
+            (None, None) => quote! { () },
+        };
+
+        quote! {
+            fn #method_ident(&self, #(#params),*)
+                -> impl std::future::Future<Output = srpc::Result<#ret_type>> + Send
+        }
+    }
+
+    /// Returns the parameters as they are declared in a signature. Their '#[serde]' attributes
+    /// only apply to the fields of 'Args'.
+    fn signature_params(&self) -> Vec<syn::PatType> {
+        self.params
+            .iter()
+            .map(|param| {
+                let mut param = (*param).clone();
+                param.attrs.retain(|attr| !attr.path.is_ident("serde"));
+                param
+            })
+            .collect()
+    }
+
+    /// Generates the method of the client stub. The stub keeps the client in its 'client' field.
+    pub fn expand(&self, vis: Option<&syn::Visibility>) -> TokenStream {
+        let method_ident = self.ident;
+        let method_name = &self.name;
+        let fields = &self.params;
+        let params = self.signature_params();
+        let param_names = self.params.iter().map(|param| &param.pat);
+        let is_notif = self.is_notif;
+
+        // Small trick to make serde work
+        let (args_struct, args) = if params.is_empty() {
+            (quote! {}, quote! { serde_json::Value::Null })
+        } else {
+            (
+                quote! {
+                    #[derive(serde::Serialize)]
+                    struct Args { #(#fields,)* }
+                },
+                quote! { serde_json::to_value(Args { #(#param_names,)* }).unwrap() },
+            )
+        };
+
+        let request = quote! {
+            srpc::json_rpc::Request::new(
+                String::from(#method_name),
+                #args,
+                None /* Id is handled in "client.call()" */
+            )
+        };
+
+        if let Some(item) = self.subscription_item {
+            return quote! {
+                #vis async fn #method_ident(&self, #(#params),*)
+                    -> srpc::Result<srpc::client::Subscription<#item>> {
+
+                    #args_struct
+                    self.client.subscribe(#request).await
+                }
+            };
+        }
+
+        match self.return_type {
+            None => quote! {
+                #vis async fn #method_ident(&self, #(#params),*) -> srpc::Result<()> {
+
+                    #args_struct
+                    let request = #request;
+
+                    if #is_notif {
+                        let _ = self.client.notify(request).await?;
+                    } else {
+                        let _ = self.client.call(request).await?;
+                    }
+
+                    Ok(())
+                }
+            },
+            Some(ret_type) => {
+                // Methods returning 'Result<T, E>' get their application errors back as 'E'.
+                // Any other error means that the call itself failed.
+                let decode_response = if is_result(ret_type) {
+                    quote! {
+                        match response.error {
+                            Some(error) => match srpc::json_rpc::ApplicationError::from_error(error) {
+                                Ok(error) => Ok(Err(error)),
+                                Err(error) => Err(error.into()),
+                            },
+                            None => Ok(Ok(serde_json::from_value(
+                                response.result.unwrap_or(serde_json::Value::Null)
+                            )?)),
+                        }
+                    }
+                } else {
+                    quote! {
+                        if response.error.is_some() {
+                            Err(response.error.unwrap().into())
+                        } else {
+                            Ok(serde_json::from_value(
+                                response.result.unwrap_or(serde_json::Value::Null)
+                            )?)
+                        }
+                    }
+                };
+
+                quote! {
+                    #vis async fn #method_ident(&self, #(#params),*) -> srpc::Result<#ret_type> {
+
+                        #args_struct
+                        let response = self.client.call(#request).await?;
+
+                        #decode_response
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,487 @@
+//! Code generation of a client from an OpenRPC document.
+//!
+//! The document is turned into a trait as it would be written for `#[srpc::client]`, and the
+//! types that the JSON Schemas describe are generated as serde structs and enums next to it.
+
+use {
+    crate::client,
+    proc_macro2::TokenStream,
+    quote::{format_ident, quote},
+    serde_json::Value,
+    std::{
+        collections::{HashMap, HashSet},
+        path::Path,
+    },
+};
+
+pub(crate) fn expand(path: syn::LitStr) -> syn::Result<TokenStream> {
+    // Paths are relative to the crate that includes the client, like in 'include_str!'.
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap_or_default();
+    let full_path = Path::new(&manifest_dir).join(path.value());
+    let text = std::fs::read_to_string(&full_path).map_err(|err| {
+        syn::Error::new(
+            path.span(),
+            format!("Could not read '{}': {}", full_path.display(), err),
+        )
+    })?;
+    let document: Value = serde_json::from_str(&text).map_err(|err| {
+        syn::Error::new(path.span(), format!("Invalid OpenRPC document: {}", err))
+    })?;
+    let error = |message: &str| syn::Error::new(path.span(), message);
+
+    let title = document["info"]["title"]
+        .as_str()
+        .ok_or_else(|| error("The document has no 'info.title'"))?;
+    if camel_case(title).is_empty() {
+        return Err(error("'info.title' has no alphanumeric characters"));
+    }
+    let trait_ident = ident(&camel_case(title));
+    let methods = document["methods"]
+        .as_array()
+        .ok_or_else(|| error("The document has no 'methods'"))?;
+
+    let mut types = TypeGenerator::new();
+    if let Some(schemas) = document["components"]["schemas"].as_object() {
+        // References use the names of the schemas, so they can not be made unique.
+        let mut names = HashMap::new();
+        for name in schemas.keys() {
+            let type_name = camel_case(name);
+            if type_name.is_empty() {
+                return Err(error(&format!(
+                    "Schema '{}' has no alphanumeric characters",
+                    name
+                )));
+            }
+            if let Some(other) = names.insert(type_name.clone(), name) {
+                return Err(error(&format!(
+                    "Schemas '{}' and '{}' have the same Rust name '{}'",
+                    other, name, type_name
+                )));
+            }
+            types.reserve(&type_name);
+        }
+        for (name, schema) in schemas {
+            types.define(&camel_case(name), schema);
+        }
+    }
+
+    let names = methods
+        .iter()
+        .map(|method| {
+            method["name"]
+                .as_str()
+                .ok_or_else(|| error("A method has no 'name'"))
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+    let method_idents = unique_idents(names.iter().map(|name| snake_case(name)), "method", "_");
+
+    let methods = methods
+        .iter()
+        .zip(names)
+        .zip(method_idents)
+        .map(|((method, name), method_ident)| {
+            let docs = doc_attr(&method["description"]);
+
+            let params = method["params"]
+                .as_array()
+                .map(|params| &params[..])
+                .unwrap_or_default()
+                .iter()
+                .map(|param| {
+                    let param_name = param["name"]
+                        .as_str()
+                        .ok_or_else(|| error("A parameter has no 'name'"))?;
+                    // Parameters are sent by name, so they can not be renamed.
+                    let param_ident = syn::parse_str::<syn::Ident>(param_name).map_err(|_| {
+                        error(&format!(
+                            "Parameter '{}' of '{}' is not a valid Rust identifier",
+                            param_name, name
+                        ))
+                    })?;
+                    let hint = format!("{}{}", camel_case(name), camel_case(param_name));
+                    let ty = types.rust_type(&param["schema"], &hint);
+                    Ok(
+                        if param["required"].as_bool().unwrap_or(false)
+                            || is_nullable(&param["schema"])
+                        {
+                            quote! { #param_ident: #ty }
+                        } else {
+                            quote! {
+                                #[serde(skip_serializing_if = "Option::is_none")]
+                                #param_ident: Option<#ty>
+                            }
+                        },
+                    )
+                })
+                .collect::<syn::Result<Vec<_>>>()?;
+
+            // Methods without a result are notifications.
+            Ok(match method.get("result") {
+                Some(result) => {
+                    let hint = format!("{}Result", camel_case(name));
+                    let ret_type = types.rust_type(&result["schema"], &hint);
+                    quote! {
+                        #docs
+                        #[rpc(name = #name)]
+                        async fn #method_ident(#(#params),*) -> #ret_type;
+                    }
+                }
+                None => quote! {
+                    #docs
+                    #[rpc(name = #name)]
+                    #[notification]
+                    async fn #method_ident(#(#params),*);
+                },
+            })
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    let docs = doc_attr(&document["info"]["description"]);
+    let client = client::expand(
+        TokenStream::new(),
+        syn::parse2(quote! {
+            #docs
+            pub trait #trait_ident {
+                #(#methods)*
+            }
+        })?,
+    )?;
+    let types = types.types;
+    let full_path = full_path.display().to_string();
+
+    Ok(quote! {
+        // Rebuilds the client when the document changes.
+        const _: &[u8] = include_bytes!(#full_path);
+
+        #(#types)*
+
+        #client
+    })
+}
+
+/// Generates the Rust types of JSON Schemas.
+struct TypeGenerator {
+    types: Vec<TokenStream>,
+    /// Names of the generated types, they have to be unique.
+    names: HashSet<String>,
+    /// Structs whose fields are being generated, references to them are boxed.
+    defining: Vec<String>,
+}
+
+impl TypeGenerator {
+    fn new() -> Self {
+        Self {
+            types: Vec::new(),
+            names: HashSet::new(),
+            defining: Vec::new(),
+        }
+    }
+
+    /// Reserves the name of a type which is defined later, so that it can be referenced.
+    fn reserve(&mut self, name: &str) {
+        self.names.insert(String::from(name));
+    }
+
+    /// Defines a named type of the schema, the type itself is used if it is not a struct or
+    /// an enum.
+    fn define(&mut self, name: &str, schema: &Value) {
+        let type_ident = ident(name);
+        let docs = doc_attr(&schema["description"]);
+
+        if let Some(variants) = string_enum(schema) {
+            let variant_idents = unique_idents(
+                variants.iter().map(|variant| camel_case(variant)),
+                "Value",
+                "",
+            );
+            let variants = variants
+                .iter()
+                .zip(variant_idents)
+                .map(|(variant, variant_ident)| {
+                    quote! {
+                        #[serde(rename = #variant)]
+                        #variant_ident
+                    }
+                });
+            self.types.push(quote! {
+                #docs
+                #[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+                pub enum #type_ident {
+                    #(#variants),*
+                }
+            });
+        } else if let Some(properties) = schema["properties"].as_object() {
+            let required: HashSet<_> = schema["required"]
+                .as_array()
+                .map(|required| required.iter().filter_map(Value::as_str).collect())
+                .unwrap_or_default();
+            let field_idents = unique_idents(
+                properties.keys().map(|field| snake_case(field)),
+                "field",
+                "_",
+            );
+            self.defining.push(String::from(name));
+            let fields: Vec<_> = properties
+                .iter()
+                .zip(field_idents)
+                .map(|((field, field_schema), field_ident)| {
+                    let field_docs = doc_attr(&field_schema["description"]);
+                    let ty = self.rust_type(field_schema, &format!("{}{}", name, camel_case(field)));
+                    if required.contains(field.as_str()) || is_nullable(field_schema) {
+                        quote! {
+                            #field_docs
+                            #[serde(rename = #field)]
+                            pub #field_ident: #ty
+                        }
+                    } else {
+                        quote! {
+                            #field_docs
+                            #[serde(rename = #field, default, skip_serializing_if = "Option::is_none")]
+                            pub #field_ident: Option<#ty>
+                        }
+                    }
+                })
+                .collect();
+            self.defining.pop();
+            self.types.push(quote! {
+                #docs
+                #[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+                pub struct #type_ident {
+                    #(#fields),*
+                }
+            });
+        } else {
+            let ty = self.rust_type_of(schema, name);
+            self.types.push(quote! {
+                #docs
+                pub type #type_ident = #ty;
+            });
+        }
+    }
+
+    /// Returns the Rust type of the schema. Structs and enums of the schema are defined with a
+    /// name derived from 'hint'.
+    fn rust_type(&mut self, schema: &Value, hint: &str) -> TokenStream {
+        if string_enum(schema).is_some() || schema["properties"].is_object() {
+            let name = self.unique_name(hint);
+            self.define(&name, schema);
+            let type_ident = ident(&name);
+            quote! { #type_ident }
+        } else {
+            self.rust_type_of(schema, hint)
+        }
+    }
+
+    fn rust_type_of(&mut self, schema: &Value, hint: &str) -> TokenStream {
+        if let Some(reference) = schema["$ref"].as_str() {
+            let name = reference.rsplit('/').next().unwrap_or(reference);
+            let name = camel_case(name);
+            let type_ident = ident(&name);
+            // A struct can only contain itself behind a pointer.
+            return if self.defining.contains(&name) {
+                quote! { Box<#type_ident> }
+            } else {
+                quote! { #type_ident }
+            };
+        }
+
+        // 'T' or null
+        for key in &["anyOf", "oneOf"] {
+            if let Some(schemas) = schema[key].as_array() {
+                if let [a, b] = &schemas[..] {
+                    let (inner, null) = if is_null(a) { (b, a) } else { (a, b) };
+                    if is_null(null) {
+                        let inner = self.rust_type(inner, hint);
+                        return quote! { Option<#inner> };
+                    }
+                }
+            }
+        }
+        if let Some(schemas) = schema["allOf"].as_array() {
+            if let [inner] = &schemas[..] {
+                return self.rust_type(inner, hint);
+            }
+        }
+
+        let (ty, nullable) = match &schema["type"] {
+            Value::String(ty) => (ty.as_str(), false),
+            Value::Array(types) => match &types[..] {
+                [Value::String(a), Value::String(b)] if b == "null" => (a.as_str(), true),
+                [Value::String(a), Value::String(b)] if a == "null" => (b.as_str(), true),
+                _ => ("", false),
+            },
+            _ => ("", false),
+        };
+        let rust_type = match ty {
+            "string" => quote! { String },
+            "boolean" => quote! { bool },
+            "null" => quote! { () },
+            "integer" => match schema["format"].as_str() {
+                Some("int8") => quote! { i8 },
+                Some("int16") => quote! { i16 },
+                Some("int32") => quote! { i32 },
+                Some("uint8") => quote! { u8 },
+                Some("uint16") => quote! { u16 },
+                Some("uint32") => quote! { u32 },
+                Some("uint64") | Some("uint") => quote! { u64 },
+                _ => quote! { i64 },
+            },
+            "number" => match schema["format"].as_str() {
+                Some("float") => quote! { f32 },
+                _ => quote! { f64 },
+            },
+            "array" => match &schema["items"] {
+                Value::Array(items) => {
+                    let items = items
+                        .iter()
+                        .enumerate()
+                        .map(|(i, item)| self.rust_type(item, &format!("{}{}", hint, i)))
+                        .collect::<Vec<_>>();
+                    quote! { (#(#items,)*) }
+                }
+                Value::Null => quote! { Vec<serde_json::Value> },
+                items => {
+                    let item =
+                        self.indirect(|types| types.rust_type(items, &format!("{}Item", hint)));
+                    quote! { Vec<#item> }
+                }
+            },
+            "object" => match &schema["additionalProperties"] {
+                Value::Object(_) => {
+                    let value = self.indirect(|types| {
+                        types.rust_type(&schema["additionalProperties"], &format!("{}Value", hint))
+                    });
+                    quote! { std::collections::HashMap<String, #value> }
+                }
+                _ => quote! { serde_json::Map<String, serde_json::Value> },
+            },
+            _ => quote! { serde_json::Value },
+        };
+
+        if nullable {
+            quote! { Option<#rust_type> }
+        } else {
+            rust_type
+        }
+    }
+
+    /// Generates a type which is stored on the heap, so it needs no 'Box' to contain the
+    /// structs that are being defined.
+    fn indirect(&mut self, generate: impl FnOnce(&mut Self) -> TokenStream) -> TokenStream {
+        let defining = std::mem::take(&mut self.defining);
+        let ty = generate(self);
+        self.defining = defining;
+        ty
+    }
+
+    fn unique_name(&mut self, hint: &str) -> String {
+        let mut name = String::from(hint);
+        let mut i = 1;
+        while !self.names.insert(name.clone()) {
+            i += 1;
+            name = format!("{}{}", hint, i);
+        }
+        name
+    }
+}
+
+/// Returns the variants if the schema is an enum of strings.
+fn string_enum(schema: &Value) -> Option<Vec<&str>> {
+    let variants = schema["enum"].as_array()?;
+    variants.iter().map(Value::as_str).collect()
+}
+
+fn is_null(schema: &Value) -> bool {
+    schema["type"] == "null"
+}
+
+/// Checks if the schema allows null, such schemas are already generated as 'Option<T>'.
+fn is_nullable(schema: &Value) -> bool {
+    let any_null = |key: &str| {
+        schema[key]
+            .as_array()
+            .is_some_and(|schemas| schemas.len() == 2 && schemas.iter().any(is_null))
+    };
+    any_null("anyOf")
+        || any_null("oneOf")
+        || schema["type"]
+            .as_array()
+            .is_some_and(|types| types.len() == 2 && types.iter().any(|ty| ty == "null"))
+}
+
+fn doc_attr(description: &Value) -> TokenStream {
+    match description.as_str() {
+        Some(description) => quote! { #[doc = #description] },
+        None => quote! {},
+    }
+}
+
+/// Returns the identifier, keywords and invalid names get a '_' suffix or prefix.
+fn ident(name: &str) -> syn::Ident {
+    match syn::parse_str::<syn::Ident>(name) {
+        Ok(ident) => ident,
+        Err(_) if name.starts_with(|c: char| c.is_ascii_digit()) => format_ident!("_{}", name),
+        Err(_) => format_ident!("{}_", name),
+    }
+}
+
+/// Returns the identifiers of names which are renamed with serde or 'rpc(name)'. Names without
+/// alphanumeric characters get the fallback with their index, e.g. 'Value0', and names that
+/// are taken get a number suffix, e.g. 'm_s' and 'm_s_2'.
+fn unique_idents(
+    names: impl Iterator<Item = String>,
+    fallback: &str,
+    separator: &str,
+) -> Vec<syn::Ident> {
+    let mut taken = HashSet::new();
+    names
+        .enumerate()
+        .map(|(index, name)| {
+            let name = if name.is_empty() {
+                format!("{}{}{}", fallback, separator, index)
+            } else {
+                name
+            };
+            let mut unique = ident(&name);
+            let mut i = 1;
+            while !taken.insert(unique.to_string()) {
+                i += 1;
+                unique = ident(&format!("{}{}{}", name, separator, i));
+            }
+            unique
+        })
+        .collect()
+}
+
+/// 'textDocument/didOpen' => 'text_document_did_open'
+fn snake_case(name: &str) -> String {
+    let mut snake = String::new();
+    let mut prev_lower = false;
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() {
+            if c.is_ascii_uppercase() && prev_lower {
+                snake.push('_');
+            }
+            snake.push(c.to_ascii_lowercase());
+            prev_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+        } else {
+            if !snake.is_empty() && !snake.ends_with('_') {
+                snake.push('_');
+            }
+            prev_lower = false;
+        }
+    }
+    snake.trim_end_matches('_').to_string()
+}
+
+/// 'text_document/didOpen' => 'TextDocumentDidOpen'
+fn camel_case(name: &str) -> String {
+    name.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            let first = chars.next().unwrap().to_ascii_uppercase();
+            std::iter::once(first).chain(chars).collect::<String>()
+        })
+        .collect()
+}
@@ -0,0 +1,122 @@
+//! Code generation for an interface shared by the client and the server.
+
+use {
+    crate::{
+        attrs::{check_method_names, MacroArgs, MethodAttrs},
+        client::{client_stub, ClientMethod},
+        openrpc,
+        service::{dispatch, DispatchArm},
+        utils::{collect_results, return_type},
+    },
+    proc_macro2::TokenStream,
+    quote::{format_ident, quote},
+};
+
+pub(crate) fn expand(attrs: TokenStream, mut input: syn::ItemTrait) -> syn::Result<TokenStream> {
+    let args = MacroArgs::parse("srpc::interface", attrs)?;
+
+    let method_attrs = collect_results(input.items.iter_mut().map(|item| match item {
+        syn::TraitItem::Method(item_method) => MethodAttrs::take(&mut item_method.attrs),
+        _ => Err(syn::Error::new_spanned(
+            item,
+            "Only methods are allowed in an srpc interface.",
+        )),
+    }))?;
+
+    let vis = &input.vis;
+    let mut match_arms = Vec::new();
+    let mut client_methods = Vec::new();
+    let mut names = Vec::new();
+    let mut descriptions = Vec::new();
+    for (item, attrs) in input.items.iter().zip(&method_attrs) {
+        if let syn::TraitItem::Method(item_method) = item {
+            let sig = &item_method.sig;
+            // Both the implementor and the client stub are used through a reference.
+            match sig.receiver() {
+                Some(syn::FnArg::Receiver(receiver))
+                    if receiver.reference.is_some() && receiver.mutability.is_none() => {}
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        &sig.ident,
+                        "Methods of an srpc interface must take '&self'",
+                    ))
+                }
+            }
+
+            let arm = DispatchArm::parse(sig, attrs, &args)?;
+            names.extend(arm.names());
+            match_arms.push(arm.expand());
+            if args.openrpc.is_some() {
+                descriptions.push(openrpc::describe_method(&arm, &item_method.attrs));
+            }
+            client_methods.push(
+                ClientMethod::new(
+                    &sig.ident,
+                    arm.params().to_vec(),
+                    return_type(&sig.output),
+                    attrs,
+                    &args,
+                )?
+                .expand(Some(vis)),
+            );
+        }
+    }
+
+    check_method_names(names.into_iter())?;
+    if args.openrpc.is_some() {
+        let description = openrpc::describe(&input.ident, &input.attrs, &descriptions);
+        input.items.push(syn::parse_quote! {
+            /// OpenRPC description of the service.
+            const OPENRPC: srpc::openrpc::ServiceDescription = #description;
+        });
+        match_arms.push(openrpc::discover_arm());
+    }
+    let dispatch = dispatch(&match_arms);
+    let client_ident = format_ident!("{}Client", input.ident);
+    let stub = client_stub(vis, &client_ident);
+
+    // Async methods return a 'Send' future since the server runs them on any thread.
+    for item in input.items.iter_mut() {
+        if let syn::TraitItem::Method(item_method) = item {
+            let sig = &mut item_method.sig;
+            if sig.asyncness.take().is_some() {
+                let output = match return_type(&sig.output) {
+                    Some(ret_type) => quote! { #ret_type },
+                    None => quote! { () },
+                };
+                sig.output = syn::parse_quote! {
+                    -> impl std::future::Future<Output = #output> + Send
+                };
+                if let Some(block) = item_method.default.as_mut() {
+                    *block = syn::parse_quote! { { async move #block } };
+                }
+            }
+        }
+    }
+
+    input.items.push(syn::parse_quote! {
+        /// Calls the RPC method with the given name. It is passed to 'srpc::server::Server'.
+        fn caller(self: std::sync::Arc<Self>,
+                  context: std::sync::Arc<srpc::server::Context>,
+                  fn_name: String,
+                  args: serde_json::Value)
+            -> std::pin::Pin<Box<dyn std::future::Future<Output = std::result::Result<serde_json::Value, srpc::json_rpc::Error>> + Send>>
+        where
+            Self: Sized + Send + Sync + 'static,
+        {
+            Box::pin(async move {
+                #dispatch
+            })
+        }
+    });
+
+    Ok(quote! {
+        #input
+
+        #stub
+
+        impl #client_ident {
+            #(#client_methods)*
+        }
+    })
+}
@@ -1,195 +1,106 @@
 extern crate proc_macro;
 
-use {proc_macro::TokenStream, quote::quote, syn::parse_macro_input};
+mod attrs;
+mod client;
+mod include_client;
+mod interface;
+mod openrpc;
+mod service;
+mod utils;
+
+use {proc_macro::TokenStream, syn::parse_macro_input};
 
 /// Generate RPC calls.
+///
+/// Methods are called by their name. `#[rpc(name = "..")]` renames a method on the wire, and
+/// `#[srpc::client(namespace = "..")]` prefixes every name with the namespace followed by
+/// `namespace_separator`, which is `_` by default.
+///
+/// Methods marked with `#[subscription]` are declared to return `impl Stream<Item = T>`, and
+/// the stub returns an `srpc::client::Subscription<T>` for them.
+///
+/// `#[serde(..)]` attributes of the parameters apply to the params that are sent, e.g.
+/// `#[serde(skip_serializing_if = "Option::is_none")]` leaves out a parameter which is `None`.
+///
 /// # Example
 /// ```no_run
+/// #[srpc::client]
 /// trait Service {
 ///     async fn foo(data: i32) -> i32;
 /// }
 /// ```
+/// The methods are called through the stub, e.g. `ServiceClient::new(client).foo(3).await?`.
 /// # Expansion
 /// ```no_run
-/// struct Service;
+/// // The trait is kept, so code that is generic over it can be tested with a test double.
+/// trait Service {
+///     fn foo(&self, data: i32) -> impl Future<Output = srpc::Result<i32>> + Send;
+/// }
+///
+/// #[derive(Clone)]
+/// struct ServiceClient {
+///     client: srpc::client::Client,
+/// }
+///
+/// impl ServiceClient {
+///     fn new(client: srpc::client::Client) -> Self {
+///         Self { client }
+///     }
+///
+///     /// Returns the underlying client.
+///     fn client(&self) -> &srpc::client::Client {
+///         &self.client
+///     }
+/// }
 ///
-/// impl Service {
-///     async fn foo(client: &srpc::client::Client, data: i32) -> srpc::Result<i32> {
+/// impl Service for ServiceClient {
+///     async fn foo(&self, data: i32) -> srpc::Result<i32> {
 ///         // Small trick to make serde work
 ///         #[derive(serde::Serialize)]
-///         struct Args { data: i32 };
+///         struct Args { data: i32 }
 ///
-///         let response = client.call(
+///         let response = self.client.call(
 ///             srpc::json_rpc::Request::new(
-///                 String::from(stringify!(#method_ident)),
-///                 serde_json::to_value(Args { #(#param_names,)* }).unwrap(),
+///                 String::from("foo"),
+///                 serde_json::to_value(Args { data }).unwrap(),
 ///                 None /* Id is handled in client.call */
-///             )).await;
+///             )).await?;
 ///
 ///         if response.error.is_some() {
 ///             Err(response.error.unwrap().into())
 ///         } else {
-///             if response.result.is_some() {
-///                 Ok(serde_json::from_value(response.result.unwrap())?)
-///             } else {
-///                 Err(srpc::json_rpc::Error::new(
-///                         srpc::json_rpc::ErrorKind::InternalError,
-///                         None).into())
-///             }
+///             Ok(serde_json::from_value(
+///                 response.result.unwrap_or(serde_json::Value::Null)
+///             )?)
 ///         }
 ///     }
 /// }
 /// ```
 #[proc_macro_attribute]
-pub fn client(_attrs: TokenStream, input: TokenStream) -> TokenStream {
+pub fn client(attrs: TokenStream, input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as syn::ItemTrait);
 
-    let self_ident = &input.ident;
-    let methods = input.items.iter().map(|item| {
-        if let syn::TraitItem::Method(item_method) = item {
-            let mut is_notif = false;
-            if !item_method.attrs.is_empty() {
-                for attr in &item_method.attrs {
-                    if let Some(segment) = attr.path.segments.first() {
-                        if segment.ident == "notification" && attr.path.segments.len() == 1 {
-                            is_notif = true;
-                            break;
-                        }
-                    }
-                }
-            }
-            let method_args = &item_method.sig.inputs;
-            let method_ident = &item_method.sig.ident;
-
-            // There is no point to have 'self' in an RPC method and for simplicity's sake,
-            // it is ignored.
-            let param_names = method_args.iter().map(|param| {
-                if let syn::FnArg::Typed(param) = param {
-                    &param.pat
-                } else {
-                    panic!("Using 'self' in an RPC call is not allowed for now.");
-                }
-            });
-
-            let mut return_type = None;
-            if let syn::ReturnType::Type(_, ret_type) = &item_method.sig.output {
-                if let syn::Type::Tuple(tuple) = ret_type.as_ref() {
-                    if !tuple.elems.is_empty() {
-                        return_type = Some(ret_type);
-                    }
-                } else {
-                    return_type = Some(ret_type);
-                }
-            }
-
-            if is_notif && return_type.is_some() {
-                panic!("Notification functions should return ()");
-            }
-
-            if method_args.is_empty() && return_type.is_none() {
-                quote! {
-                    async fn #method_ident(client: &srpc::client::Client)
-                        -> srpc::Result<()> {
-
-                        let request = srpc::json_rpc::Request::new(
-                            String::from(stringify!(#method_ident)),
-                            serde_json::Value::Null,
-                            None /* Id is handled in "client.call()" */
-                        );
-
-                        if #is_notif {
-                            let _ = client.notify(request).await?;
-                        } else {
-                            let _ = client.call(request).await?;
-                        }
-
-                        Ok(())
-                    }
-                }
-            } else if method_args.is_empty() && return_type.is_some() {
-                let ret_type = return_type.unwrap();
-                quote! {
-                    async fn #method_ident(client: &srpc::client::Client)
-                        -> srpc::Result<#ret_type> {
-                        let response = client.call(srpc::json_rpc::Request::new(
-                                String::from(stringify!(#method_ident)),
-                                serde_json::Value::Null,
-                                None
-                            )).await?;
-
-                        if response.error.is_some() {
-                            Err(response.error.unwrap().into())
-                        } else {
-                            Ok(serde_json::from_value(
-                                response.result.unwrap_or(serde_json::Value::Null)
-                            )?)
-                        }
-                    }
-                }
-            } else if !method_args.is_empty() && return_type.is_none() {
-                quote! {
-                    async fn #method_ident(client: &srpc::client::Client, #method_args)
-                        -> srpc::Result<()> {
-
-                        #[derive(serde::Serialize)]
-                        struct Args { #method_args }
-
-                        let request = srpc::json_rpc::Request::new(
-                            String::from(stringify!(#method_ident)),
-                            serde_json::to_value(Args { #(#param_names,)* }).unwrap(),
-                            None
-                        );
-
-                        if #is_notif {
-                            let _ = client.notify(request).await?;
-                        } else {
-                            let _ = client.call(request).await?;
-                        }
-
-                        Ok(())
-                    }
-                }
-            } else {
-                let ret_type = return_type.unwrap();
-                quote! {
-                    async fn #method_ident(client: &srpc::client::Client, #method_args)
-                        -> srpc::Result<#ret_type> {
-
-                        #[derive(serde::Serialize)]
-                        struct Args { #method_args }
-
-                        let response = client.call(
-                            srpc::json_rpc::Request::new(
-                                String::from(stringify!(#method_ident)),
-                                serde_json::to_value(Args { #(#param_names,)* }).unwrap(),
-                                None
-                            )).await?;
-
-                        if response.error.is_some() {
-                            Err(response.error.unwrap().into())
-                        } else {
-                                Ok(serde_json::from_value(
-                                        response.result.unwrap_or(serde_json::Value::Null)
-                                        )?)
-                        }
-                    }
-                }
-            }
-        } else {
-            panic!("Only methods are allowed in an srpc client.");
-        }
-    });
-
-    TokenStream::from(quote! {
-        struct #self_ident;
-        impl #self_ident {
-            #(#methods)*
-        }
-    })
+    client::expand(attrs.into(), input)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
 }
 
-/// Generates an RPC service. Note that RPC methods must be defined as
-/// 'async' for now.
+/// Generates an RPC service. RPC methods can be either 'async' or not, and they can take
+/// '&self', 'self: Arc<Self>' or no receiver at all.
+///
+/// Methods are named the same way as in [client](macro@client). A method can also be reached
+/// by the complete names given with `#[rpc(alias = "..")]`, e.g. a deprecated name. The
+/// namespace is not prepended to aliases.
+///
+/// With `#[srpc::service(openrpc)]`, the service is described by an OpenRPC document in the
+/// `OPENRPC` constant and the built-in `rpc.discover` method returns it. See `srpc::openrpc`.
+///
+/// Methods marked with `#[subscription]` return a `Stream`, or a `Result` of one. The caller
+/// gets the id of the subscription and the items are sent to it as notifications, see
+/// `srpc::server::subscribe`.
+///
+/// Calls are aborted when the caller cancels them or closes the connection. Methods that must
+/// run to the end regardless, e.g. because they write data, are marked with `#[detached]`.
 ///
 /// # Example
 /// ```no_run
@@ -206,181 +117,171 @@ pub fn client(_attrs: TokenStream, input: TokenStream) -> TokenStream {
 ///
 /// # Expansion
 ///```no_run
-///struct StrService;                                                                            
+///struct StrService;
 ///impl StrService {
 ///    async fn contains(data: String, elem: String) -> bool {
 ///        data.contains(&elem)
 ///    }
 ///}
 ///impl StrService {
-///    async fn call(fn_name: String, args: serde_json::Value) -> srpc::Result<serde_json::Value> {
+///    async fn call(self: Arc<Self>,
+///                  context: Arc<srpc::server::Context>,
+///                  fn_name: String,
+///                  args: serde_json::Value)
+///        -> Result<serde_json::Value, srpc::json_rpc::Error> {
 ///        Ok(match fn_name.as_str() {
 ///            "contains" => {
+///                #[derive(serde::Deserialize)]
 ///                struct Args {
 ///                    data: String,
 ///                    elem: String,
 ///                }
-///                let Args { data, elem } = serde_json::from_value(args)?;
-///                serde_json::to_value(&StrService::contains(data, elem).await)?
-///             }
-///             _ => return Err(String::new().into()),
+///                let params: Args = match serde_json::from_value(args) {
+///                    Ok(params) => params,
+///                    Err(e) => return Err(/* InvalidParams */),
+///                };
+///                // The dispatcher calls the method itself, so it stays callable directly.
+///                serde_json::to_value(Self::contains(params.data, params.elem).await).unwrap()
+///            }
+///            _ => return Err(/* MethodNotFound */),
 ///        })
 ///    }
-///     
-///    fn caller(
-///        fn_name: String,
-///        args: serde_json::Value,
-///  ) -> std::pin::Pin<Box<dyn std::future::Future<Output = srpc::Result<serde_json::Value>> + Send>>
-///
-///    {
-///        Box::pin(Self::call(fn_name, args))
+///
+///    fn caller(self: Arc<Self>,
+///              context: Arc<srpc::server::Context>,
+///              fn_name: String,
+///              args: serde_json::Value)
+///        -> Pin<Box<dyn Future<Output = Result<serde_json::Value, srpc::json_rpc::Error>> + Send>> {
+///        Box::pin(self.call(context, fn_name, args))
 ///    }
 ///}
 ///```
 #[proc_macro_attribute]
-pub fn service(_attrs: TokenStream, input: TokenStream) -> TokenStream {
+pub fn service(attrs: TokenStream, input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as syn::ItemImpl);
-    let self_ident = &input.self_ty;
-    let match_arms = input.items.iter().map(|item| {
-        if let syn::ImplItem::Method(item_method) = item {
-            let method_args = &item_method.sig.inputs;
-            let method_ident = &item_method.sig.ident;
-            let method_block = &item_method.block;
-
-            // We only get Typed parameters and ignore 'self' because there is no
-            // point to have 'self' in the parameters and it also breaks the code.
-            let mut param_names = Vec::new();
-            method_args.iter().for_each(|param| {
-                if let syn::FnArg::Typed(param) = param {
-                    // Get the identifier of the parameter
-                    if let syn::Pat::Ident(ref param_ident) = *param.pat {
-                        if param_ident.ident == "self" || param_ident.ident == "context" {
-                        } else {
-                            param_names.push(&param.pat);
-                        }
-                    }
-                    else {
-                        panic!("Unexpected ident");
-                    }
-                } else {
-                    panic!("'self' can only be used in format 'self: Arc<Self>' in an RPC call is not allowed for now.");
-                }
-            });
-
-            let args = method_args.iter().map(|param| {
-                if let syn::FnArg::Typed(param) = param {
-                    // Get the identifier of the parameter
-                    if let syn::Pat::Ident(ref param_ident) = *param.pat {
-                        if param_ident.ident == "self" || param_ident.ident == "context" {
-                            None
-                        } else {
-                            Some(param)
-                        }
-                    }
-                    else {
-                        panic!("Unexpected ident");
-                    }
-                } else {
-                    panic!("'self' can only be used in format 'self: Arc<Self>' in an RPC call is not allowed for now.");
-                }
-            }).filter(|param| param.is_some());
 
-            let mut return_type = None;
-            if let syn::ReturnType::Type(_, ret_type) = &item_method.sig.output {
-                if let syn::Type::Tuple(tuple) = ret_type.as_ref() {
-                    if !tuple.elems.is_empty() {
-                        return_type = Some(ret_type);
-                    }
-                } else {
-                    return_type = Some(ret_type);
-                }
-            }
-
-            // Generating the match arms
-            if param_names.is_empty() && return_type.is_none() {
-                quote! {
-                    stringify!(#method_ident) => {
-                        
-                        async move { #method_block }.await;
-                        
-                        serde_json::Value::Null
-                    }
-                }
-            } else if param_names.is_empty() && return_type.is_some() {
-                quote! {
-                    stringify!(#method_ident) => {
-                        serde_json::to_value(async move {
-                            #method_block
-                        }.await).unwrap()
-                    }
-                }
-            } else if !param_names.is_empty() && return_type.is_none() {
-                quote! {
-                    stringify!(#method_ident) => {
-                        #[derive(serde::Deserialize)]
-                        struct Args { #(#args,)* };
-                        let Args { #(#param_names,)* } = match serde_json::from_value(args) {
-                            Ok(args) => args,
-                            Err(e) => return Err(srpc::json_rpc::Error::new(
-                                                srpc::json_rpc::ErrorKind::InvalidParams,
-                                                Some(serde_json::to_value(e.to_string()).unwrap()))),
-                        };
-                        
-                        async move {
-                            #method_block  
-                        }.await;
-                        
-                        serde_json::Value::Null
-                    }
-                }
-            } else {
-                quote! {
-                    stringify!(#method_ident) => {
-                        #[derive(serde::Deserialize)]
-                        struct Args { #(#args,)* };
-                        let Args { #(#param_names,)* } = match serde_json::from_value(args) {
-                            Ok(args) => args,
-                            Err(e) => return Err(srpc::json_rpc::Error::new(
-                                                srpc::json_rpc::ErrorKind::InvalidParams,
-                                                Some(serde_json::to_value(e.to_string()).unwrap())))
-                        };
-                        
-                        serde_json::to_value(async move {
-                            #method_block
-                        }.await).unwrap()
-                    }
-                }
-            }
-        } else {
-            panic!("Items other than function are not supported right now.");
-        }
-    });
-    let q = quote! {
-        #input
-        impl #self_ident {
-            async fn call(self: Arc<Self>,
-                          context: Arc<srpc::server::Context>,
-                          fn_name: String,
-                          args: serde_json::Value)
-                -> std::result::Result<serde_json::Value, srpc::json_rpc::Error> {
-
-                Ok(match fn_name.as_str() {
-                    #(#match_arms,)*
-                    _ => return Err(srpc::json_rpc::Error::new(srpc::json_rpc::ErrorKind::MethodNotFound, None)),
-                })
-            }
+    service::expand(attrs.into(), input)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
 
-            fn caller(self: Arc<Self>,
-                      context: Arc<srpc::server::Context>,
-                      fn_name: String,
-                      args: serde_json::Value)
-                -> std::pin::Pin<Box<dyn std::future::Future<Output = std::result::Result<serde_json::Value, srpc::json_rpc::Error>> + Send>> {
+/// Generates both sides of an RPC service from a single trait, so that the client and the
+/// server can not drift apart.
+///
+/// The trait itself becomes the server side trait to implement, and a client stub named
+/// `<Trait>Client` is generated next to it. Methods take `&self` and the reserved `context`
+/// parameter, just like in [service](macro@service). `#[notification]`, `#[blocking]`,
+/// `#[detached]`, `#[subscription]` and `#[rpc(..)]` are supported as well, and the namespace is given with
+/// `#[srpc::interface(namespace = "..")]`. `#[srpc::interface(openrpc)]` adds the `OPENRPC`
+/// constant and the `rpc.discover` method to the trait.
+///
+/// Subscriptions are declared to return `impl Stream<Item = T> + Send + 'static`, since the
+/// stream outlives the call.
+///
+/// # Example
+/// ```no_run
+/// #[srpc::interface]
+/// pub trait StrService {
+///     async fn contains(&self, data: String, elem: String) -> bool;
+///
+///     #[notification]
+///     async fn set_data(&self, context: Arc<Context>, is_cool: bool);
+/// }
+///
+/// struct MyService;
+///
+/// impl StrService for MyService {
+///     async fn contains(&self, data: String, elem: String) -> bool {
+///         data.contains(&elem)
+///     }
+///
+///     async fn set_data(&self, context: Arc<Context>, is_cool: bool) {}
+/// }
+///
+/// let server = Server::new(MyService, MyService::caller);
+/// let contains = StrServiceClient::new(client).contains(data, elem).await?;
+/// ```
+///
+/// # Expansion
+/// ```no_run
+/// pub trait StrService {
+///     fn contains(&self, data: String, elem: String) -> impl Future<Output = bool> + Send;
+///
+///     fn set_data(&self, context: Arc<Context>, is_cool: bool)
+///         -> impl Future<Output = ()> + Send;
+///
+///     fn caller(self: Arc<Self>,
+///               context: Arc<srpc::server::Context>,
+///               fn_name: String,
+///               args: serde_json::Value)
+///         -> Pin<Box<dyn Future<Output = Result<serde_json::Value, srpc::json_rpc::Error>> + Send>>
+///     where
+///         Self: Sized + Send + Sync + 'static,
+///     {
+///         // Same dispatcher as the one of #[srpc::service]
+///     }
+/// }
+///
+/// // Same stub as the one of #[srpc::client]
+/// #[derive(Clone)]
+/// pub struct StrServiceClient {
+///     client: srpc::client::Client,
+/// }
+/// impl StrServiceClient {
+///     pub fn new(client: srpc::client::Client) -> Self { /* .. */ }
+///
+///     pub async fn contains(&self, data: String, elem: String) -> srpc::Result<bool> { /* .. */ }
+///
+///     pub async fn set_data(&self, is_cool: bool) -> srpc::Result<()> { /* .. */ }
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn interface(attrs: TokenStream, input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as syn::ItemTrait);
 
-                Box::pin(self.call(context, fn_name, args))
+    interface::expand(attrs.into(), input)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
 
-            }
-        }
-    };
+/// Generates a client from an [OpenRPC](https://open-rpc.org) document, e.g. the document of a
+/// service written in another language. The path is relative to the root of the crate.
+///
+/// The document becomes a trait named after `info.title`, which is expanded by
+/// [client](macro@client). Methods are named in snake case and renamed back with `#[rpc(name)]`,
+/// and methods without a result are notifications. Types described by the JSON Schemas in the
+/// document are generated as serde structs and enums.
+///
+/// # Example
+/// ```no_run
+/// srpc::include_client!("api/geometry.json");
+///
+/// let geometry = GeometryClient::new(client);
+/// let distance = geometry.geo_distance(Point { x: 3, y: 4 }, None).await?;
+/// ```
+///
+/// # Expansion
+/// ```no_run
+/// #[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+/// pub struct Point {
+///     #[serde(rename = "x")]
+///     pub x: i32,
+///     #[serde(rename = "y")]
+///     pub y: i32,
+/// }
+///
+/// #[srpc::client]
+/// pub trait Geometry {
+///     /// Returns the distance between two points.
+///     #[rpc(name = "geo_distance")]
+///     async fn geo_distance(from: Point, to: Option<Point>) -> f64;
+/// }
+/// ```
+#[proc_macro]
+pub fn include_client(input: TokenStream) -> TokenStream {
+    let path = parse_macro_input!(input as syn::LitStr);
 
-    TokenStream::from(q)
+    include_client::expand(path)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
 }
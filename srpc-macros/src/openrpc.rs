@@ -0,0 +1,88 @@
+//! Code generation for the OpenRPC description of a service.
+
+use {
+    crate::{
+        service::DispatchArm,
+        utils::{doc_comment, is_option, result_ok_type, return_type},
+    },
+    proc_macro2::TokenStream,
+    quote::quote,
+};
+
+/// Generates the 'srpc::openrpc::ServiceDescription' of the described methods. Doc comments of
+/// the service become its description.
+pub(crate) fn describe(
+    title: &syn::Ident,
+    attrs: &[syn::Attribute],
+    methods: &[TokenStream],
+) -> TokenStream {
+    let title = title.to_string();
+    let description = option(doc_comment(attrs));
+
+    quote! {
+        srpc::openrpc::ServiceDescription {
+            title: #title,
+            version: env!("CARGO_PKG_VERSION"),
+            description: #description,
+            methods: &[#(#methods),*],
+        }
+    }
+}
+
+/// Generates the 'srpc::openrpc::MethodDescription' of a method, 'attrs' being the attributes
+/// of the method.
+pub(crate) fn describe_method(arm: &DispatchArm, attrs: &[syn::Attribute]) -> TokenStream {
+    let name = arm.name();
+    let aliases = arm.aliases();
+    let description = option(doc_comment(attrs));
+    let params = arm.params().iter().map(|param| {
+        let name = match &*param.pat {
+            syn::Pat::Ident(pat_ident) => pat_ident.ident.to_string(),
+            _ => unreachable!("parameters are checked by the dispatcher"),
+        };
+        let ty = &param.ty;
+        let required = !is_option(ty);
+        quote! {
+            srpc::openrpc::ParamDescription {
+                name: #name,
+                required: #required,
+                schema: srpc::openrpc::schemars::gen::SchemaGenerator::subschema_for::<#ty>,
+            }
+        }
+    });
+    // Application errors are not a part of the result, and subscriptions result in the id of
+    // the subscription.
+    let result = match return_type(&arm.sig().output) {
+        _ if arm.is_subscription() => quote! { u64 },
+        Some(ret_type) => match result_ok_type(ret_type) {
+            Some(ok_type) => quote! { #ok_type },
+            None => quote! { #ret_type },
+        },
+        None => quote! { () },
+    };
+
+    quote! {
+        srpc::openrpc::MethodDescription {
+            name: #name,
+            aliases: &[#(#aliases),*],
+            description: #description,
+            params: &[#(#params),*],
+            result: srpc::openrpc::schemars::gen::SchemaGenerator::subschema_for::<#result>,
+        }
+    }
+}
+
+/// Generates the match arm of the built-in 'rpc.discover' method. 'Self::OPENRPC' has to be
+/// in scope.
+pub(crate) fn discover_arm() -> TokenStream {
+    quote! {
+        "rpc.discover" => serde_json::to_value(Self::OPENRPC.document()).unwrap()
+    }
+}
+
+fn option(value: Option<String>) -> TokenStream {
+    match value {
+        Some(value) => quote! { Some(#value) },
+        None => quote! { None },
+    }
+}
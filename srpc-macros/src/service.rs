@@ -0,0 +1,312 @@
+//! Code generation for the server side.
+
+use {
+    crate::{
+        attrs::{check_method_names, MacroArgs, MethodAttrs},
+        openrpc,
+        utils::{collect_results, is_result, param_ident, return_type},
+    },
+    proc_macro2::TokenStream,
+    quote::quote,
+};
+
+pub(crate) fn expand(attrs: TokenStream, mut input: syn::ItemImpl) -> syn::Result<TokenStream> {
+    let args = MacroArgs::parse("srpc::service", attrs)?;
+
+    // srpc attributes are not real attributes, so they are removed from the methods.
+    let method_attrs = collect_results(input.items.iter_mut().map(|item| match item {
+        syn::ImplItem::Method(item_method) => {
+            let attrs = MethodAttrs::take(&mut item_method.attrs)?;
+            MethodAttrs::reject(
+                &attrs.notification,
+                "#[notification] can only be used in an srpc client",
+            )?;
+            Ok(attrs)
+        }
+        _ => Ok(MethodAttrs::default()),
+    }))?;
+
+    let self_ident = &input.self_ty;
+    let (impl_generics, _, where_clause) = input.generics.split_for_impl();
+    let arms = collect_results(input.items.iter().zip(&method_attrs).map(|(item, attrs)| {
+        if let syn::ImplItem::Method(item_method) = item {
+            DispatchArm::parse(&item_method.sig, attrs, &args)
+        } else {
+            Err(syn::Error::new_spanned(
+                item,
+                "Items other than function are not supported right now.",
+            ))
+        }
+    }))?;
+    check_method_names(arms.iter().flat_map(DispatchArm::names))?;
+    let mut match_arms: Vec<_> = arms.iter().map(DispatchArm::expand).collect();
+
+    let description = if args.openrpc.is_some() {
+        let title = match &*input.self_ty {
+            syn::Type::Path(type_path) => &type_path.path.segments.last().unwrap().ident,
+            self_ty => {
+                return Err(syn::Error::new_spanned(
+                    self_ty,
+                    "'openrpc' can only be used for a named type",
+                ))
+            }
+        };
+        let methods: Vec<_> = arms
+            .iter()
+            .zip(input.items.iter().filter_map(|item| match item {
+                syn::ImplItem::Method(item_method) => Some(&item_method.attrs),
+                _ => None,
+            }))
+            .map(|(arm, attrs)| openrpc::describe_method(arm, attrs))
+            .collect();
+        let description = openrpc::describe(title, &input.attrs, &methods);
+        match_arms.push(openrpc::discover_arm());
+        quote! {
+            /// OpenRPC description of the service.
+            pub const OPENRPC: srpc::openrpc::ServiceDescription = #description;
+        }
+    } else {
+        quote! {}
+    };
+    let dispatch = dispatch(&match_arms);
+
+    let q = quote! {
+        #input
+        impl #impl_generics #self_ident #where_clause {
+            #description
+
+            async fn call(self: Arc<Self>,
+                          context: Arc<srpc::server::Context>,
+                          fn_name: String,
+                          args: serde_json::Value)
+                -> std::result::Result<serde_json::Value, srpc::json_rpc::Error> {
+
+                #dispatch
+            }
+
+            fn caller(self: Arc<Self>,
+                      context: Arc<srpc::server::Context>,
+                      fn_name: String,
+                      args: serde_json::Value)
+                -> std::pin::Pin<Box<dyn std::future::Future<Output = std::result::Result<serde_json::Value, srpc::json_rpc::Error>> + Send>> {
+
+                Box::pin(self.call(context, fn_name, args))
+
+            }
+        }
+    };
+
+    Ok(q)
+}
+
+/// Matches the method name against the match arms. 'self', 'context', 'fn_name' and 'args'
+/// have to be in scope.
+pub(crate) fn dispatch(match_arms: &[TokenStream]) -> TokenStream {
+    quote! {
+        Ok(match fn_name.as_str() {
+            #(#match_arms,)*
+            _ => return Err(srpc::json_rpc::Error::new(srpc::json_rpc::ErrorKind::MethodNotFound, None)),
+        })
+    }
+}
+
+/// The match arm of the dispatcher which calls an RPC method.
+pub(crate) struct DispatchArm<'a> {
+    sig: &'a syn::Signature,
+    attrs: &'a MethodAttrs,
+    /// Name of the method on the wire, followed by its aliases
+    names: Vec<String>,
+    /// Parameters that are sent by the client
+    params: Vec<&'a syn::PatType>,
+    /// Arguments that the method is called with
+    call_args: Vec<TokenStream>,
+}
+
+impl<'a> DispatchArm<'a> {
+    pub fn parse(
+        sig: &'a syn::Signature,
+        attrs: &'a MethodAttrs,
+        args: &MacroArgs,
+    ) -> syn::Result<Self> {
+        // Reserved parameters are passed by the server and the rest of the parameters
+        // are sent by the client.
+        let mut params = Vec::new();
+        let call_args = collect_results(sig.inputs.iter().map(|param| match param {
+            syn::FnArg::Typed(param) => {
+                let ident = param_ident(param)?;
+                if ident == "self" {
+                    Ok(quote! { self })
+                } else if ident == "context" && attrs.subscription.is_some() {
+                    // The subscription needs the context after the call.
+                    Ok(quote! { context.clone() })
+                } else if ident == "context" {
+                    Ok(quote! { context })
+                } else {
+                    params.push(param);
+                    Ok(quote! { params.#ident })
+                }
+            }
+            // Only shared access to the service is possible since the same instance
+            // serves every request.
+            syn::FnArg::Receiver(receiver)
+                if receiver.reference.is_some() && receiver.mutability.is_none() =>
+            {
+                Ok(quote! { &*self })
+            }
+            syn::FnArg::Receiver(receiver) => Err(syn::Error::new_spanned(
+                receiver,
+                "'self' can only be used in format '&self' or 'self: Arc<Self>' in an RPC call",
+            )),
+        }))?;
+
+        if let (Some(attr), Some(_)) = (&attrs.blocking, sig.asyncness) {
+            return Err(syn::Error::new_spanned(
+                attr,
+                "#[blocking] can only be used on methods that are not 'async'",
+            ));
+        }
+
+        if let (Some(attr), Some(_)) = (&attrs.blocking, &attrs.subscription) {
+            return Err(syn::Error::new_spanned(
+                attr,
+                "#[blocking] can not be used on subscriptions",
+            ));
+        }
+        if let (Some(attr), Some(_)) = (&attrs.detached, &attrs.subscription) {
+            return Err(syn::Error::new_spanned(
+                attr,
+                "#[detached] can not be used on subscriptions",
+            ));
+        }
+        if let (Some(attr), None) = (&attrs.subscription, return_type(&sig.output)) {
+            return Err(syn::Error::new_spanned(
+                attr,
+                "#[subscription] can only be used on methods that return a Stream",
+            ));
+        }
+
+        let names = std::iter::once(args.method_name(&sig.ident, attrs))
+            .chain(attrs.aliases.iter().map(syn::LitStr::value))
+            .collect();
+
+        Ok(Self {
+            sig,
+            attrs,
+            names,
+            params,
+            call_args,
+        })
+    }
+
+    pub fn sig(&self) -> &'a syn::Signature {
+        self.sig
+    }
+
+    /// Name of the method on the wire.
+    pub fn name(&self) -> &str {
+        &self.names[0]
+    }
+
+    /// Other names that the method is called by.
+    pub fn aliases(&self) -> &[String] {
+        &self.names[1..]
+    }
+
+    /// Checks if the method is called through 'srpc::server::subscribe'.
+    pub fn is_subscription(&self) -> bool {
+        self.attrs.subscription.is_some()
+    }
+
+    /// Parameters that are sent by the client.
+    pub fn params(&self) -> &[&'a syn::PatType] {
+        &self.params
+    }
+
+    /// Names that the method is called by, paired with the method for error reporting.
+    pub fn names(&self) -> impl Iterator<Item = (&'a syn::Ident, String)> + '_ {
+        let ident = &self.sig.ident;
+        self.names.iter().map(move |name| (ident, name.clone()))
+    }
+
+    pub fn expand(&self) -> TokenStream {
+        let method_ident = &self.sig.ident;
+        let names = &self.names;
+        let params = &self.params;
+        let call_args = &self.call_args;
+
+        let parse_params = if params.is_empty() {
+            quote! {}
+        } else {
+            quote! {
+                #[derive(serde::Deserialize)]
+                struct Args { #(#params,)* }
+                let params: Args = match serde_json::from_value(args) {
+                    Ok(params) => params,
+                    Err(e) => return Err(srpc::json_rpc::Error::new(
+                                        srpc::json_rpc::ErrorKind::InvalidParams,
+                                        Some(serde_json::to_value(e.to_string()).unwrap()))),
+                };
+            }
+        };
+
+        let call = match (&self.attrs.blocking, self.sig.asyncness) {
+            // Blocking methods run on the blocking thread pool, so they don't starve the
+            // other requests.
+            (Some(_), _) => quote! {
+                match srpc::server::spawn_blocking(context.clone(), move || {
+                    Self::#method_ident(#(#call_args),*)
+                }).await {
+                    Ok(result) => result,
+                    Err(err) => return Err(err),
+                }
+            },
+            (None, Some(_)) => quote! { Self::#method_ident(#(#call_args),*).await },
+            (None, None) => quote! { Self::#method_ident(#(#call_args),*) },
+        };
+
+        // Methods returning 'Result<T, E>' send 'E' back as a JSON-RPC error. Subscriptions send
+        // back the id of the subscription.
+        let call_and_encode = match return_type(&self.sig.output) {
+            Some(ret_type) if self.is_subscription() && is_result(ret_type) => quote! {
+                match #call {
+                    Ok(stream) => srpc::server::subscribe(&context, stream)?,
+                    Err(err) => return Err(srpc::json_rpc::ApplicationError::into_error(err)),
+                }
+            },
+            Some(_) if self.is_subscription() => quote! {{
+                let stream = #call;
+                srpc::server::subscribe(&context, stream)?
+            }},
+            None => quote! {
+                #call;
+                serde_json::Value::Null
+            },
+            Some(ret_type) if is_result(ret_type) => quote! {
+                match #call {
+                    Ok(result) => serde_json::to_value(result).unwrap(),
+                    Err(err) => return Err(srpc::json_rpc::ApplicationError::into_error(err)),
+                }
+            },
+            Some(_) => quote! { serde_json::to_value(#call).unwrap() },
+        };
+
+        if self.attrs.detached.is_some() {
+            // The arm runs in a task of its own, which is not aborted with the call.
+            return quote! {
+                #(#names)|* => {
+                    srpc::server::detach(async move {
+                        #parse_params
+                        Ok({ #call_and_encode })
+                    }).await?
+                }
+            };
+        }
+
+        quote! {
+            #(#names)|* => {
+                #parse_params
+                #call_and_encode
+            }
+        }
+    }
+}
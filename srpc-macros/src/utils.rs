@@ -0,0 +1,137 @@
+//! Helpers shared by the macros.
+
+/// Returns the return type of the signature. '()' is treated as no return type.
+pub(crate) fn return_type(output: &syn::ReturnType) -> Option<&syn::Type> {
+    if let syn::ReturnType::Type(_, ret_type) = output {
+        if let syn::Type::Tuple(tuple) = ret_type.as_ref() {
+            if tuple.elems.is_empty() {
+                return None;
+            }
+        }
+        Some(ret_type)
+    } else {
+        None
+    }
+}
+
+/// Checks if the type is written as 'Result<T, E>'. Such methods report application errors
+/// through 'E', see 'srpc::json_rpc::ApplicationError'.
+pub(crate) fn is_result(ty: &syn::Type) -> bool {
+    generic_types(ty, "Result").is_some_and(|types| types.len() == 2)
+}
+
+/// Returns 'T' if the type is written as 'Result<T, E>'.
+pub(crate) fn result_ok_type(ty: &syn::Type) -> Option<&syn::Type> {
+    generic_types(ty, "Result")
+        .filter(|types| types.len() == 2)
+        .map(|types| types[0])
+}
+
+/// Checks if the type is written as 'Option<T>'. Such parameters can be left out.
+pub(crate) fn is_option(ty: &syn::Type) -> bool {
+    generic_types(ty, "Option").is_some_and(|types| types.len() == 1)
+}
+
+/// Returns 'T' if the type is written as 'impl Stream<Item = T>'.
+pub(crate) fn stream_item_type(ty: &syn::Type) -> Option<&syn::Type> {
+    let bounds = match ty {
+        syn::Type::ImplTrait(impl_trait) => &impl_trait.bounds,
+        _ => return None,
+    };
+    bounds.iter().find_map(|bound| match bound {
+        syn::TypeParamBound::Trait(bound) => {
+            let segment = bound.path.segments.last()?;
+            match &segment.arguments {
+                syn::PathArguments::AngleBracketed(args) if segment.ident == "Stream" => {
+                    args.args.iter().find_map(|arg| match arg {
+                        syn::GenericArgument::Binding(binding) if binding.ident == "Item" => {
+                            Some(&binding.ty)
+                        }
+                        _ => None,
+                    })
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    })
+}
+
+/// Returns the generic type arguments if the last segment of the type is 'name<..>'.
+fn generic_types<'a>(ty: &'a syn::Type, name: &str) -> Option<Vec<&'a syn::Type>> {
+    if let syn::Type::Path(type_path) = ty {
+        if let Some(segment) = type_path.path.segments.last() {
+            if let syn::PathArguments::AngleBracketed(args) = &segment.arguments {
+                if segment.ident == name {
+                    return Some(
+                        args.args
+                            .iter()
+                            .filter_map(|arg| match arg {
+                                syn::GenericArgument::Type(ty) => Some(ty),
+                                _ => None,
+                            })
+                            .collect(),
+                    );
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Returns the doc comment in the attributes, without the leading space of each line.
+pub(crate) fn doc_comment(attrs: &[syn::Attribute]) -> Option<String> {
+    let lines: Vec<_> = attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("doc"))
+        .filter_map(|attr| match attr.parse_meta() {
+            Ok(syn::Meta::NameValue(syn::MetaNameValue {
+                lit: syn::Lit::Str(lit),
+                ..
+            })) => Some(lit.value()),
+            _ => None,
+        })
+        .map(|line| line.strip_prefix(' ').map(String::from).unwrap_or(line))
+        .collect();
+
+    if lines.is_empty() {
+        None
+    } else {
+        Some(lines.join("\n").trim().to_string())
+    }
+}
+
+/// Returns the identifier of an RPC parameter. Parameters are sent by name, so patterns
+/// other than plain identifiers are not allowed.
+pub(crate) fn param_ident(param: &syn::PatType) -> syn::Result<&syn::Ident> {
+    if let syn::Pat::Ident(ref pat_ident) = *param.pat {
+        Ok(&pat_ident.ident)
+    } else {
+        Err(syn::Error::new_spanned(
+            &param.pat,
+            "RPC parameters must be plain identifiers",
+        ))
+    }
+}
+
+/// Collects the results into a vector. Unlike collecting into a `Result` directly, every error
+/// is combined into one so that all problems are reported at once.
+pub(crate) fn collect_results<T>(
+    results: impl Iterator<Item = syn::Result<T>>,
+) -> syn::Result<Vec<T>> {
+    let mut values = Vec::new();
+    let mut error: Option<syn::Error> = None;
+    for result in results {
+        match result {
+            Ok(value) => values.push(value),
+            Err(err) => match error.as_mut() {
+                Some(error) => error.combine(err),
+                None => error = Some(err),
+            },
+        }
+    }
+    match error {
+        Some(error) => Err(error),
+        None => Ok(values),
+    }
+}
@@ -0,0 +1,128 @@
+use {
+    serde_json::json,
+    srpc::{client::Client, server::Server, transport::Transport},
+    std::{sync::Arc, time::Duration},
+    tokio::{
+        io::AsyncWriteExt,
+        net::TcpStream,
+        sync::{mpsc, Notify},
+    },
+};
+
+mod common;
+
+#[srpc::client]
+trait Jobs {
+    async fn run(millis: u64) -> u64;
+}
+
+struct JobServer {
+    events: mpsc::UnboundedSender<&'static str>,
+    // Lets 'save' finish
+    saved: Arc<Notify>,
+}
+
+// Reports whether the job is finished or aborted when it is dropped.
+struct Job {
+    events: mpsc::UnboundedSender<&'static str>,
+    finished: bool,
+}
+
+impl Drop for Job {
+    fn drop(&mut self) {
+        let event = if self.finished { "finished" } else { "aborted" };
+        let _ = self.events.send(event);
+    }
+}
+
+#[srpc::service]
+impl JobServer {
+    async fn run(&self, millis: u64) -> u64 {
+        let mut job = Job {
+            events: self.events.clone(),
+            finished: false,
+        };
+        let _ = self.events.send("started");
+        tokio::time::sleep(Duration::from_millis(millis)).await;
+        job.finished = true;
+        millis
+    }
+
+    #[detached]
+    async fn save(&self) {
+        let _ = self.events.send("saving");
+        self.saved.notified().await;
+        let _ = self.events.send("saved");
+    }
+}
+
+fn job_server() -> (
+    Server<JobServer>,
+    mpsc::UnboundedReceiver<&'static str>,
+    Arc<Notify>,
+) {
+    let (events, received) = mpsc::unbounded_channel();
+    let saved = Arc::new(Notify::new());
+    let service = JobServer {
+        events,
+        saved: saved.clone(),
+    };
+    (Server::new(service, JobServer::caller), received, saved)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn dropped_call_is_cancelled_on_server() {
+    let (server, mut received, _) = job_server();
+    let addr = common::serve(server).await;
+
+    let jobs = JobsClient::new(Client::new(addr, Arc::new(Transport::new())));
+    assert_eq!(jobs.run(10).await.unwrap(), 10);
+    assert_eq!(received.recv().await.unwrap(), "started");
+    assert_eq!(received.recv().await.unwrap(), "finished");
+
+    // The call is dropped once the server runs it.
+    let call = tokio::spawn({
+        let jobs = jobs.clone();
+        async move { jobs.run(10_000).await }
+    });
+    assert_eq!(received.recv().await.unwrap(), "started");
+    call.abort();
+    assert_eq!(received.recv().await.unwrap(), "aborted");
+
+    // The connection is still usable after the cancellation.
+    assert_eq!(jobs.run(10).await.unwrap(), 10);
+    assert_eq!(received.recv().await.unwrap(), "started");
+    assert_eq!(received.recv().await.unwrap(), "finished");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn calls_are_aborted_when_caller_disconnects() {
+    let (server, mut received, saved) = job_server();
+    let addr = common::serve(server).await;
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    let requests = [
+        json!({ "jsonrpc": "2.0", "method": "run", "params": { "millis": 10_000 }, "id": 1 }),
+        json!({ "jsonrpc": "2.0", "method": "save", "params": null, "id": 2 }),
+    ];
+    for request in &requests {
+        let data = serde_json::to_vec(request).unwrap();
+        stream
+            .write_all(&(data.len() as u32).to_le_bytes())
+            .await
+            .unwrap();
+        stream.write_all(&data).await.unwrap();
+    }
+    let mut started = vec![
+        received.recv().await.unwrap(),
+        received.recv().await.unwrap(),
+    ];
+    started.sort_unstable();
+    assert_eq!(started, vec!["saving", "started"]);
+    drop(stream);
+
+    // The detached call runs to the end.
+    assert_eq!(received.recv().await.unwrap(), "aborted");
+    saved.notify_one();
+    assert_eq!(received.recv().await.unwrap(), "saved");
+}
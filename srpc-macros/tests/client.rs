@@ -0,0 +1,133 @@
+use {
+    srpc::{client::Client, server::Server, transport::Transport},
+    std::sync::Arc,
+    tokio::sync::mpsc,
+};
+
+mod common;
+
+#[srpc::client]
+trait StrService {
+    async fn contains(data: String, elem: String) -> bool;
+
+    async fn len(&self, data: String) -> usize;
+
+    #[notification]
+    async fn set_data(is_cool: bool);
+}
+
+struct StrServer {
+    data: mpsc::UnboundedSender<bool>,
+}
+
+#[srpc::service]
+impl StrServer {
+    fn contains(data: String, elem: String) -> bool {
+        data.contains(&elem)
+    }
+
+    fn len(data: String) -> usize {
+        data.len()
+    }
+
+    fn set_data(&self, is_cool: bool) {
+        let _ = self.data.send(is_cool);
+    }
+}
+
+/// Code under test only depends on the trait.
+async fn count_libs<S: StrService>(service: &S, data: Vec<String>) -> usize {
+    let mut count = 0;
+    for data in data {
+        if service.contains(data, String::from("lib")).await.unwrap() {
+            count += 1;
+        }
+    }
+    count
+}
+
+struct StrServiceDouble;
+
+impl StrService for StrServiceDouble {
+    async fn contains(&self, data: String, elem: String) -> srpc::Result<bool> {
+        Ok(data.ends_with(&elem))
+    }
+
+    async fn len(&self, _data: String) -> srpc::Result<usize> {
+        Err("not used".into())
+    }
+
+    async fn set_data(&self, _is_cool: bool) -> srpc::Result<()> {
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_double() {
+    let data = vec![String::from("cool lib"), String::from("lib is cool")];
+    assert_eq!(count_libs(&StrServiceDouble, data).await, 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn stub_calls_server() {
+    let (data, mut received) = mpsc::unbounded_channel();
+    let addr = common::serve(Server::new(StrServer { data }, StrServer::caller)).await;
+
+    let client = Client::new(addr, Arc::new(Transport::new()));
+    let service = StrServiceClient::new(client.clone());
+
+    service.set_data(true).await.unwrap();
+    assert!(received.recv().await.unwrap());
+    assert_eq!(service.len(String::from("cool")).await.unwrap(), 4);
+
+    let data = vec![String::from("cool lib"), String::from("lib is cool")];
+    assert_eq!(count_libs(&service, data).await, 2);
+
+    // Stubs created from clones of a client share the connection.
+    let other = StrServiceClient::new(service.client().clone());
+    assert!(other
+        .contains(String::from("cool lib"), String::from("cool"))
+        .await
+        .unwrap());
+}
+
+#[srpc::client(namespace = "str", namespace_separator = "/")]
+trait TextService {
+    #[rpc(name = "byteLength")]
+    async fn byte_len(data: String) -> usize;
+
+    async fn upper(data: String) -> String;
+}
+
+#[srpc::client]
+trait LegacyTextService {
+    async fn len(data: String) -> usize;
+}
+
+struct TextServer;
+
+#[srpc::service(namespace = "str", namespace_separator = "/")]
+impl TextServer {
+    #[rpc(name = "byteLength", alias = "len")]
+    fn byte_len(data: String) -> usize {
+        data.len()
+    }
+
+    fn upper(data: String) -> String {
+        data.to_uppercase()
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn renamed_methods() {
+    let addr = common::serve(Server::new(TextServer, TextServer::caller)).await;
+
+    let client = Client::new(addr, Arc::new(Transport::new()));
+    let service = TextServiceClient::new(client.clone());
+    assert_eq!(service.byte_len(String::from("cool")).await.unwrap(), 4);
+    assert_eq!(service.upper(String::from("cool")).await.unwrap(), "COOL");
+
+    // Aliases are not namespaced.
+    let legacy = LegacyTextServiceClient::new(client);
+    assert_eq!(legacy.len(String::from("cool lib")).await.unwrap(), 8);
+}
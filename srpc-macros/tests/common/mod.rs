@@ -0,0 +1,10 @@
+use {srpc::server::Server, std::net::SocketAddr, tokio::net::TcpListener};
+
+/// Serves the server on a port which the OS picks, and returns its address. Connections are
+/// accepted as soon as this returns, since the listener is already bound.
+pub async fn serve<T: Send + Sync + 'static>(server: Server<T>) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(server.serve_listener(listener));
+    addr
+}
@@ -0,0 +1,50 @@
+use {
+    srpc::server::Server,
+    std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    tokio::{io::AsyncReadExt, net::TcpStream, sync::mpsc},
+};
+
+mod common;
+
+struct Echo;
+
+#[srpc::service]
+impl Echo {
+    fn echo(data: String) -> String {
+        data
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn hooks_run_on_connect_and_disconnect() {
+    let (events, mut received) = mpsc::unbounded_channel();
+    let connections = Arc::new(AtomicUsize::new(0));
+
+    let mut server = Server::new(Echo, Echo::caller);
+    let connect_events = events.clone();
+    server.on_connect(move |context| {
+        // Only the first connection is accepted.
+        let accepted = connections.fetch_add(1, Ordering::SeqCst) == 0;
+        let _ = connect_events.send(format!("connect {} {}", context.connection_id, accepted));
+        accepted
+    });
+    server.on_disconnect(move |context| {
+        let _ = events.send(format!("disconnect {}", context.connection_id));
+    });
+    let addr = common::serve(server).await;
+
+    let accepted = TcpStream::connect(addr).await.unwrap();
+    assert_eq!(received.recv().await.unwrap(), "connect 1 true");
+
+    let mut refused = TcpStream::connect(addr).await.unwrap();
+    assert_eq!(received.recv().await.unwrap(), "connect 2 false");
+    // The server closes the refused connection.
+    assert_eq!(refused.read(&mut [0; 8]).await.unwrap(), 0);
+
+    drop(accepted);
+    assert_eq!(received.recv().await.unwrap(), "disconnect 1");
+    assert!(received.try_recv().is_err());
+}
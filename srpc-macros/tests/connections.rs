@@ -0,0 +1,72 @@
+use {
+    serde_json::{json, Value},
+    srpc::{client::Client, server::Server, transport::Transport},
+    std::sync::Arc,
+    tokio::sync::mpsc,
+};
+
+mod common;
+
+#[srpc::client]
+trait Echo {
+    async fn echo(data: String) -> String;
+}
+
+struct EchoServer;
+
+#[srpc::service]
+impl EchoServer {
+    fn echo(data: String) -> String {
+        data
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn server_notifies_and_disconnects_connections() {
+    let (events, mut disconnected) = mpsc::unbounded_channel();
+    let mut server = Server::new(EchoServer, EchoServer::caller);
+    server.on_disconnect(move |context| {
+        let _ = events.send(context.connection_id);
+    });
+    let connections = server.connections();
+    let addr = common::serve(server).await;
+
+    let first = Client::new(addr, Arc::new(Transport::new()));
+    let second = Client::new(addr, Arc::new(Transport::new()));
+    let mut first_config = first.notifications("config");
+    let mut second_config = second.notifications("config");
+    let mut second_only = second.notifications("only");
+    let first = EchoClient::new(first);
+    let second = EchoClient::new(second);
+    assert_eq!(first.echo(String::from("cool")).await.unwrap(), "cool");
+    assert_eq!(second.echo(String::from("lib")).await.unwrap(), "lib");
+
+    let ids: Vec<u64> = connections
+        .list()
+        .iter()
+        .map(|context| context.connection_id)
+        .collect();
+    assert_eq!(ids, vec![1, 2]);
+
+    assert_eq!(
+        connections.broadcast("config", json!({ "v": 2 })).unwrap(),
+        2
+    );
+    assert_eq!(first_config.recv().await.unwrap(), json!({ "v": 2 }));
+    assert_eq!(second_config.recv().await.unwrap(), json!({ "v": 2 }));
+
+    connections.send_to(2, "only", "second").unwrap();
+    assert_eq!(second_only.recv().await.unwrap(), Value::from("second"));
+    assert!(connections.send_to(3, "only", "nobody").is_err());
+
+    // A context which is still held does not keep the connection open.
+    let held = connections.get(1).unwrap();
+    assert!(connections.disconnect(1));
+    assert_eq!(disconnected.recv().await.unwrap(), 1);
+    assert!(first.echo(String::from("gone")).await.is_err());
+    drop(held);
+    assert!(!connections.disconnect(1));
+    assert_eq!(connections.len(), 1);
+    assert!(connections.get(2).is_some());
+    assert_eq!(second.echo(String::from("still")).await.unwrap(), "still");
+}
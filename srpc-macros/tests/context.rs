@@ -0,0 +1,113 @@
+use {
+    serde_json::{json, Value},
+    srpc::{
+        client::Client,
+        json_rpc,
+        server::{
+            middleware::{BoxFuture, Call, Middleware, Next},
+            Context, Server,
+        },
+        transport::Transport,
+    },
+    std::sync::Arc,
+};
+
+mod common;
+
+#[srpc::client]
+trait Inspector {
+    async fn inspect() -> Value;
+}
+
+struct InspectorServer;
+
+#[srpc::service]
+impl InspectorServer {
+    fn inspect(context: Arc<Context>) -> Value {
+        json!({
+            "has_id": context.request_id.is_some(),
+            "method": context.method,
+            "connection_id": context.connection_id,
+            "local_port": context.local_addr.port(),
+            "user": context.extensions.get::<User>().map(|user| user.0),
+        })
+    }
+}
+
+struct User(&'static str);
+
+struct Auth;
+
+impl Middleware for Auth {
+    fn handle<'a>(
+        &'a self,
+        mut call: Call,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, Result<Value, json_rpc::Error>> {
+        Box::pin(async move {
+            call.context.extensions.insert(User("cool"));
+            next.run(call).await
+        })
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn context_has_connection_and_request_fields() {
+    let mut server = Server::new(InspectorServer, InspectorServer::caller);
+    server.add_middleware(Auth);
+    let addr = common::serve(server).await;
+
+    let transport = Arc::new(Transport::new());
+    let first = InspectorClient::new(Client::new(addr, transport.clone()));
+    let second = InspectorClient::new(Client::new(addr, transport));
+
+    let expected = |connection_id: u64| {
+        json!({
+            "has_id": true,
+            "method": "inspect",
+            "connection_id": connection_id,
+            "local_port": addr.port(),
+            "user": "cool",
+        })
+    };
+    assert_eq!(first.inspect().await.unwrap(), expected(1));
+    assert_eq!(second.inspect().await.unwrap(), expected(2));
+    assert_eq!(first.inspect().await.unwrap(), expected(1));
+}
+
+#[srpc::client]
+trait Accounts {
+    async fn login(name: String);
+
+    async fn whoami() -> Option<String>;
+}
+
+struct AccountServer;
+
+#[derive(Clone)]
+struct LoggedIn(String);
+
+#[srpc::service]
+impl AccountServer {
+    fn login(context: Arc<Context>, name: String) {
+        context.session.insert(LoggedIn(name));
+    }
+
+    fn whoami(context: Arc<Context>) -> Option<String> {
+        context.session.get::<LoggedIn>().map(|user| user.0)
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn session_lives_as_long_as_connection() {
+    let addr = common::serve(Server::new(AccountServer, AccountServer::caller)).await;
+
+    let transport = Arc::new(Transport::new());
+    let first = AccountsClient::new(Client::new(addr, transport.clone()));
+    let second = AccountsClient::new(Client::new(addr, transport));
+
+    assert_eq!(first.whoami().await.unwrap(), None);
+    first.login(String::from("cool")).await.unwrap();
+    assert_eq!(first.whoami().await.unwrap(), Some(String::from("cool")));
+    assert_eq!(second.whoami().await.unwrap(), None);
+}
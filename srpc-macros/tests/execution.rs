@@ -0,0 +1,106 @@
+use {
+    srpc::{
+        client::Client,
+        server::{ExecutionMode, Server},
+        transport::Transport,
+    },
+    std::{
+        collections::HashMap,
+        sync::{Arc, Mutex},
+    },
+    tokio::sync::{mpsc, Notify},
+};
+
+mod common;
+
+#[srpc::client]
+trait Settings {
+    #[notification]
+    async fn set(key: String, value: u64);
+}
+
+type Setting = (String, u64);
+
+/// Gates of the 'set' calls, by key and value.
+#[derive(Clone, Default)]
+struct Gates(Arc<Mutex<HashMap<Setting, Arc<Notify>>>>);
+
+impl Gates {
+    fn get(&self, key: &str, value: u64) -> Arc<Notify> {
+        let mut gates = self.0.lock().unwrap();
+        gates.entry((String::from(key), value)).or_default().clone()
+    }
+}
+
+struct SettingsServer {
+    events: mpsc::UnboundedSender<Setting>,
+    gates: Gates,
+}
+
+#[srpc::service]
+impl SettingsServer {
+    // Waits until its gate is opened, so the order of the gates decides the order of the
+    // values if they run concurrently.
+    async fn set(&self, key: String, value: u64) {
+        self.gates.get(&key, value).notified().await;
+        let _ = self.events.send((key, value));
+    }
+}
+
+/// Sends the values 0, 1 and 2 of each key, and opens their gates in reverse.
+async fn applied(mode: ExecutionMode, keys: &[&str]) -> mpsc::UnboundedReceiver<Setting> {
+    let (events, received) = mpsc::unbounded_channel();
+    let gates = Gates::default();
+    let mut server = Server::new(
+        SettingsServer {
+            events,
+            gates: gates.clone(),
+        },
+        SettingsServer::caller,
+    );
+    server.set_execution_mode(mode);
+    let addr = common::serve(server).await;
+
+    let settings = SettingsClient::new(Client::new(addr, Arc::new(Transport::new())));
+    for value in 0..3 {
+        for key in keys {
+            settings.set(String::from(*key), value).await.unwrap();
+        }
+    }
+    for value in (0..3).rev() {
+        for key in keys {
+            gates.get(key, value).notify_one();
+        }
+    }
+    received
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn notifications_run_in_order() {
+    let mut received = applied(ExecutionMode::SequentialNotifications, &["cool"]).await;
+    for value in 0..3 {
+        assert_eq!(
+            received.recv().await.unwrap(),
+            (String::from("cool"), value)
+        );
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn requests_with_same_key_run_in_order() {
+    let mode = ExecutionMode::by_key(|request| request.params["key"].as_str().map(String::from));
+    let mut received = applied(mode, &["cool", "lib"]).await;
+
+    let mut values = Vec::new();
+    for _ in 0..6 {
+        values.push(received.recv().await.unwrap());
+    }
+    for key in &["cool", "lib"] {
+        let key_values: Vec<u64> = values
+            .iter()
+            .filter(|(k, _)| k == key)
+            .map(|(_, value)| *value)
+            .collect();
+        assert_eq!(key_values, vec![0, 1, 2]);
+    }
+}
@@ -0,0 +1,194 @@
+use {
+    api::Geometry as _,
+    schemars::JsonSchema,
+    serde::{Deserialize, Serialize},
+    srpc::{
+        client::{
+            interceptor::{Interceptor, Next},
+            Client,
+        },
+        json_rpc::{Request, Response},
+        server::Server,
+        transport::Transport,
+        BoxFuture,
+    },
+    std::sync::Arc,
+    units::Units as _,
+};
+
+mod common;
+
+mod api {
+    srpc::include_client!("tests/api/geometry.json");
+}
+
+// Names which are empty or the same in Rust get unique identifiers, and 'Tree' contains
+// itself.
+mod units {
+    srpc::include_client!("tests/api/units.json");
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+enum Unit {
+    #[serde(rename = "m")]
+    Meter,
+    #[serde(rename = "km")]
+    Kilometer,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+struct Circle {
+    center: Point,
+    radius: f64,
+    /// Meters by default
+    #[serde(rename = "unitOfLength")]
+    unit_of_length: Option<Unit>,
+}
+
+struct Geometry;
+
+/// Calculations on points.
+#[srpc::service(openrpc, namespace = "geo")]
+impl Geometry {
+    /// Returns the distance between two points.
+    #[rpc(name = "distanceBetween")]
+    fn distance(from: Point, to: Option<Point>) -> f64 {
+        let to = to.unwrap_or(Point { x: 0, y: 0 });
+        (((from.x - to.x).pow(2) + (from.y - to.y).pow(2)) as f64).sqrt()
+    }
+
+    fn bounding_circle(points: Vec<Point>) -> Circle {
+        Circle {
+            center: Point {
+                x: points.iter().map(|p| p.x).sum::<i32>() / points.len() as i32,
+                y: points.iter().map(|p| p.y).sum::<i32>() / points.len() as i32,
+            },
+            radius: 1.0,
+            unit_of_length: Some(Unit::Kilometer),
+        }
+    }
+
+    fn reset(unit: Unit) {
+        let _ = unit;
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn generated_client_calls_server() {
+    let addr = common::serve(Server::new(Geometry, Geometry::caller)).await;
+
+    let client = Client::new(addr, Arc::new(Transport::new()));
+    let geometry = api::GeometryClient::new(client);
+
+    let distance = geometry
+        .geo_distance_between(api::Point { x: 3, y: 4 }, None)
+        .await
+        .unwrap();
+    assert_eq!(distance, 5.0);
+
+    let circle = geometry
+        .geo_bounding_circle(vec![api::Point { x: 0, y: 0 }, api::Point { x: 4, y: 2 }])
+        .await
+        .unwrap();
+    assert_eq!(
+        circle,
+        api::Circle {
+            center: api::Point { x: 2, y: 1 },
+            radius: 1.0,
+            unit_of_length: Some(api::Unit::Km),
+        }
+    );
+
+    // 'geo_reset' has no result in the document, so it is a notification.
+    geometry.geo_reset(api::Unit::M).await.unwrap();
+}
+
+#[test]
+fn clashing_names_are_unique() {
+    let speed = units::Speed {
+        field_0: 1.0,
+        m_s: 2.0,
+        m_s_2: 3.0,
+        unit: units::SpeedUnit::Value2,
+    };
+    let value = serde_json::to_value(&speed).unwrap();
+    assert_eq!(
+        value,
+        serde_json::json!({ "-": 1.0, "m s": 2.0, "m/s": 3.0, "unit": "" })
+    );
+
+    let units: Vec<units::SpeedUnit> = serde_json::from_str(r#"["m/s", "m s", "", "-"]"#).unwrap();
+    assert_eq!(
+        units,
+        vec![
+            units::SpeedUnit::MS,
+            units::SpeedUnit::MS2,
+            units::SpeedUnit::Value2,
+            units::SpeedUnit::Value3,
+        ]
+    );
+
+    // 'convert/' is generated next to 'convert'.
+    let _ = <units::UnitsClient as units::Units>::convert_2;
+}
+
+/// Answers every request with its params instead of sending it.
+struct EchoParams;
+
+impl Interceptor for EchoParams {
+    fn intercept<'a>(
+        &'a self,
+        request: Request,
+        _: Next<'a>,
+    ) -> BoxFuture<'a, srpc::Result<Option<Response>>> {
+        Box::pin(async move {
+            let Request { params, id, .. } = request;
+            Ok(id.map(|id| Response::from_result(params, id)))
+        })
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn unset_optional_params_are_not_sent() {
+    let mut client = Client::new(([127, 0, 0, 1], 1).into(), Arc::new(Transport::new()));
+    client.add_interceptor(EchoParams);
+    let units = units::UnitsClient::new(client);
+
+    let leaf = units::Tree {
+        height: 1.0,
+        parent: None,
+        next: None,
+        children: Vec::new(),
+    };
+    let tree = units::Tree {
+        height: 2.0,
+        parent: Some(Box::new(leaf.clone())),
+        next: Some(Box::new(leaf.clone())),
+        children: vec![leaf],
+    };
+    let leaf = serde_json::json!({ "height": 1.0, "next": null, "children": [] });
+    let expected = serde_json::json!({
+        "height": 2.0,
+        "parent": leaf,
+        "next": leaf,
+        "children": [leaf],
+    });
+
+    let params = units.measure(tree.clone(), None).await.unwrap();
+    assert_eq!(
+        serde_json::Value::Object(params),
+        serde_json::json!({ "tree": expected })
+    );
+
+    let params = units.measure(tree, Some(String::from("m"))).await.unwrap();
+    assert_eq!(
+        serde_json::Value::Object(params),
+        serde_json::json!({ "tree": expected, "unit": "m" })
+    );
+}
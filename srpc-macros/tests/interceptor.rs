@@ -0,0 +1,144 @@
+use {
+    serde_json::json,
+    srpc::{
+        client::{
+            interceptor::{Interceptor, Next},
+            Client,
+        },
+        json_rpc::{Request, Response},
+        server::Server,
+        transport::Transport,
+        BoxFuture,
+    },
+    std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+};
+
+mod common;
+
+#[srpc::client]
+trait Counter {
+    async fn add(n: i32) -> i32;
+
+    async fn flaky() -> usize;
+
+    async fn forbidden() -> i32;
+
+    #[notification]
+    async fn reset();
+}
+
+struct CounterServer;
+
+static FLAKY_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+#[srpc::service]
+impl CounterServer {
+    fn add(n: i32) -> i32 {
+        n + 1
+    }
+
+    /// Fails every other call.
+    fn flaky() -> Result<usize, srpc::json_rpc::Error> {
+        let calls = FLAKY_CALLS.fetch_add(1, Ordering::SeqCst) + 1;
+        if calls % 2 == 1 {
+            Err(srpc::json_rpc::Error::custom(1, "Try again", None))
+        } else {
+            Ok(calls)
+        }
+    }
+
+    fn forbidden() -> i32 {
+        unreachable!("the interceptor fails fast")
+    }
+
+    fn reset() {}
+}
+
+/// Doubles the params of 'add' and records the requests and responses.
+struct Recorder(Arc<Mutex<Vec<String>>>);
+
+impl Interceptor for Recorder {
+    fn intercept<'a>(
+        &'a self,
+        mut request: Request,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, srpc::Result<Option<Response>>> {
+        Box::pin(async move {
+            self.0.lock().unwrap().push(format!(">{}", request.method));
+            if request.method == "add" {
+                request.params["n"] = json!(request.params["n"].as_i64().unwrap() * 2);
+            }
+            let response = next.run(request).await?;
+            if let Some(response) = &response {
+                self.0
+                    .lock()
+                    .unwrap()
+                    .push(format!("<{}", response.result.clone().unwrap_or_default()));
+            }
+            Ok(response)
+        })
+    }
+}
+
+struct FailFast;
+
+impl Interceptor for FailFast {
+    fn intercept<'a>(
+        &'a self,
+        request: Request,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, srpc::Result<Option<Response>>> {
+        Box::pin(async move {
+            if request.method == "forbidden" {
+                return Err("forbidden".into());
+            }
+            next.run(request).await
+        })
+    }
+}
+
+struct Retry;
+
+impl Interceptor for Retry {
+    fn intercept<'a>(
+        &'a self,
+        request: Request,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, srpc::Result<Option<Response>>> {
+        Box::pin(async move {
+            let response = next.run(request.clone()).await?;
+            match &response {
+                Some(Response { error: Some(_), .. }) => next.run(request).await,
+                _ => Ok(response),
+            }
+        })
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn interceptors_wrap_stub_calls() {
+    let addr = common::serve(Server::new(CounterServer, CounterServer::caller)).await;
+
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let mut client = Client::new(addr, Arc::new(Transport::new()));
+    client.add_interceptor(Recorder(log.clone()));
+    client.add_interceptor(FailFast);
+    client.add_interceptor(Retry);
+    let counter = CounterClient::new(client);
+
+    assert_eq!(counter.add(2).await.unwrap(), 5);
+    assert_eq!(counter.flaky().await.unwrap(), 2);
+    assert_eq!(
+        counter.forbidden().await.unwrap_err().to_string(),
+        "forbidden"
+    );
+    counter.reset().await.unwrap();
+
+    assert_eq!(
+        *log.lock().unwrap(),
+        vec![">add", "<5", ">flaky", "<2", ">forbidden", ">reset"]
+    );
+}
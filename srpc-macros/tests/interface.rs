@@ -0,0 +1,155 @@
+use {
+    futures::{stream, Stream, StreamExt},
+    serde_json::json,
+    srpc::{
+        client::Client,
+        json_rpc::ErrorKind,
+        server::{Context, Server},
+        transport::Transport,
+    },
+    std::sync::Arc,
+};
+
+mod common;
+
+#[srpc::interface]
+pub trait StrService {
+    async fn contains(&self, data: String, elem: String) -> bool;
+
+    async fn find(&self, data: String, elem: String) -> Result<usize, srpc::json_rpc::Error>;
+
+    fn caller_port(&self, context: Arc<Context>) -> u16;
+
+    #[blocking]
+    fn count(&self, data: String, elem: String) -> usize;
+
+    #[notification]
+    async fn set_data(&self, is_cool: bool);
+
+    #[subscription]
+    fn chars(&self, data: String) -> impl Stream<Item = char> + Send + 'static;
+}
+
+struct MyService {
+    prefix: String,
+}
+
+impl StrService for MyService {
+    async fn contains(&self, data: String, elem: String) -> bool {
+        data.starts_with(&self.prefix) && data.contains(&elem)
+    }
+
+    async fn find(&self, data: String, elem: String) -> Result<usize, srpc::json_rpc::Error> {
+        data.find(&elem)
+            .ok_or_else(|| srpc::json_rpc::Error::custom(1, "Element not found", Some(json!(elem))))
+    }
+
+    fn caller_port(&self, context: Arc<Context>) -> u16 {
+        context.caller_addr.port()
+    }
+
+    fn count(&self, data: String, elem: String) -> usize {
+        data.matches(&elem).count()
+    }
+
+    async fn set_data(&self, _is_cool: bool) {}
+
+    fn chars(&self, data: String) -> impl Stream<Item = char> + Send + 'static {
+        stream::iter(data.chars().collect::<Vec<_>>())
+    }
+}
+
+fn my_service() -> Arc<MyService> {
+    Arc::new(MyService {
+        prefix: String::from("cool"),
+    })
+}
+
+fn context() -> Arc<Context> {
+    Arc::new(Context::new(([127, 0, 0, 1], 8080).into()))
+}
+
+#[tokio::test]
+async fn dispatch() {
+    let result = MyService::caller(
+        my_service(),
+        context(),
+        String::from("contains"),
+        json!({ "data": "cool lib", "elem": "lib" }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result, json!(true));
+
+    let result = MyService::caller(
+        my_service(),
+        context(),
+        String::from("caller_port"),
+        json!(null),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result, json!(8080));
+
+    let result = MyService::caller(
+        my_service(),
+        context(),
+        String::from("count"),
+        json!({ "data": "cool lib, cool rpc", "elem": "cool" }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result, json!(2));
+
+    let error = MyService::caller(
+        my_service(),
+        context(),
+        String::from("unknown"),
+        json!(null),
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(error.kind, ErrorKind::MethodNotFound);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn client_stub_calls_server() {
+    let server = Server::new(
+        MyService {
+            prefix: String::from("cool"),
+        },
+        MyService::caller,
+    );
+    let addr = common::serve(server).await;
+
+    let client = StrServiceClient::new(Client::new(addr, Arc::new(Transport::new())));
+
+    client.set_data(true).await.unwrap();
+    assert!(client
+        .contains(String::from("cool lib"), String::from("lib"))
+        .await
+        .unwrap());
+    assert_eq!(
+        client
+            .count(String::from("cool cool"), String::from("cool"))
+            .await
+            .unwrap(),
+        2
+    );
+
+    let error = client
+        .find(String::from("cool lib"), String::from("rpc"))
+        .await
+        .unwrap()
+        .unwrap_err();
+    assert_eq!(error.kind, ErrorKind::Custom(1));
+
+    let chars: Vec<_> = client
+        .chars(String::from("lib"))
+        .await
+        .unwrap()
+        .map(Result::unwrap)
+        .collect()
+        .await;
+    assert_eq!(chars, vec!['l', 'i', 'b']);
+}
@@ -0,0 +1,82 @@
+use {
+    serde_json::{json, Value},
+    srpc::{
+        client::{
+            interceptor::{Interceptor, Next},
+            Client,
+        },
+        json_rpc::{Request, Response},
+        server::{Context, Server},
+        transport::Transport,
+        BoxFuture,
+    },
+    std::sync::Arc,
+};
+
+mod common;
+
+#[srpc::client]
+trait Tenants {
+    async fn whoami() -> Value;
+}
+
+struct TenantServer;
+
+#[srpc::service]
+impl TenantServer {
+    fn whoami(context: Arc<Context>) -> Value {
+        Value::Object(context.meta.clone())
+    }
+}
+
+struct Trace;
+
+impl Interceptor for Trace {
+    fn intercept<'a>(
+        &'a self,
+        mut request: Request,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, srpc::Result<Option<Response>>> {
+        Box::pin(async move {
+            request
+                .meta
+                .get_or_insert_with(Default::default)
+                .insert(String::from("trace"), json!("abc"));
+            next.run(request).await
+        })
+    }
+}
+
+#[test]
+fn meta_is_left_out_if_there_is_none() {
+    let request = Request::new(String::from("whoami"), Value::Null, None);
+    let data = serde_json::to_value(&request).unwrap();
+    assert_eq!(data.get("meta"), None);
+
+    let request: Request = serde_json::from_value(json!({
+        "jsonrpc": "2.0",
+        "method": "whoami",
+        "params": null,
+        "id": 1,
+        "meta": { "tenant": "cool" },
+    }))
+    .unwrap();
+    assert_eq!(request.meta.unwrap()["tenant"], json!("cool"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn meta_is_passed_in_context() {
+    let addr = common::serve(Server::new(TenantServer, TenantServer::caller)).await;
+
+    let mut client = Client::new(addr, Arc::new(Transport::new())).with_meta("tenant", "cool");
+    client.add_interceptor(Trace);
+    let tenants = TenantsClient::new(client.clone());
+    assert_eq!(
+        tenants.whoami().await.unwrap(),
+        json!({ "tenant": "cool", "trace": "abc" })
+    );
+
+    client.set_send_meta(false);
+    let tenants = TenantsClient::new(client);
+    assert_eq!(tenants.whoami().await.unwrap(), json!({}));
+}
@@ -0,0 +1,56 @@
+use {
+    serde_json::{json, Value},
+    srpc::{
+        client::Client,
+        server::{Context, Server},
+        transport::Transport,
+    },
+    std::sync::{Arc, Mutex},
+};
+
+mod common;
+
+#[srpc::client]
+trait Counter {
+    async fn count(to: u32) -> u32;
+}
+
+struct CounterServer;
+
+#[srpc::service]
+impl CounterServer {
+    fn count(context: Arc<Context>, to: u32) -> u32 {
+        for i in 1..=to {
+            context.notify("tick", json!({ "i": i })).unwrap();
+        }
+        context.notify("unhandled", ()).unwrap();
+        to
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn server_notifies_caller() {
+    let mut server = Server::new(CounterServer, CounterServer::caller);
+    server.on_connect(|context| context.notify("welcome", "cool").is_ok());
+    let addr = common::serve(server).await;
+
+    let client = Client::new(addr, Arc::new(Transport::new()));
+    let ticks = Arc::new(Mutex::new(Vec::new()));
+    let ticks_clone = ticks.clone();
+    client.on_notification("tick", move |params| {
+        ticks_clone
+            .lock()
+            .unwrap()
+            .push(params["i"].as_u64().unwrap())
+    });
+    let mut welcome = client.notifications("welcome");
+
+    let counter = CounterClient::new(client);
+    assert_eq!(counter.count(3).await.unwrap(), 3);
+    assert_eq!(welcome.recv().await.unwrap(), Value::from("cool"));
+    // Notifications are sent before the response.
+    assert_eq!(*ticks.lock().unwrap(), vec![1, 2, 3]);
+
+    assert_eq!(counter.count(1).await.unwrap(), 1);
+    assert_eq!(*ticks.lock().unwrap(), vec![1, 2, 3, 1]);
+}
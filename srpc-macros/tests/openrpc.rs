@@ -0,0 +1,159 @@
+use {
+    schemars::JsonSchema,
+    serde::{Deserialize, Serialize},
+    serde_json::json,
+    srpc::{json_rpc::Error, openrpc::Document, server::Context},
+    std::sync::Arc,
+};
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+struct Geometry;
+
+/// Calculations on points.
+#[srpc::service(openrpc, namespace = "geo")]
+impl Geometry {
+    /// Returns the distance between two points.
+    ///
+    /// The origin is used if 'to' is not given.
+    #[rpc(name = "distance", alias = "dist")]
+    fn distance_between(from: Point, to: Option<Point>) -> f64 {
+        let to = to.unwrap_or(Point { x: 0, y: 0 });
+        (((from.x - to.x).pow(2) + (from.y - to.y).pow(2)) as f64).sqrt()
+    }
+
+    async fn midpoint(context: Arc<Context>, a: Point, b: Point) -> Result<Point, Error> {
+        let _ = context;
+        Ok(Point {
+            x: (a.x + b.x) / 2,
+            y: (a.y + b.y) / 2,
+        })
+    }
+
+    fn reset() {}
+
+    /// Points on the line.
+    #[subscription]
+    fn walk(from: Point) -> impl futures::Stream<Item = Point> {
+        futures::stream::iter((0..).map(move |i| Point {
+            x: from.x + i,
+            y: from.y,
+        }))
+    }
+}
+
+#[srpc::interface(openrpc)]
+pub trait Shapes {
+    /// Area of a circle.
+    fn area(&self, radius: f64) -> f64;
+}
+
+struct MyShapes;
+
+impl Shapes for MyShapes {
+    fn area(&self, radius: f64) -> f64 {
+        std::f64::consts::PI * radius * radius
+    }
+}
+
+fn context() -> Arc<Context> {
+    Arc::new(Context::new(([127, 0, 0, 1], 8080).into()))
+}
+
+#[test]
+fn service_document() {
+    let document = serde_json::to_value(Geometry::OPENRPC.document()).unwrap();
+
+    assert_eq!(document["info"]["title"], json!("Geometry"));
+    assert_eq!(
+        document["info"]["version"],
+        json!(env!("CARGO_PKG_VERSION"))
+    );
+    assert_eq!(
+        document["info"]["description"],
+        json!("Calculations on points.")
+    );
+
+    let distance = &document["methods"][0];
+    assert_eq!(distance["name"], json!("geo_distance"));
+    assert_eq!(
+        distance["description"],
+        json!(
+            "Returns the distance between two points.\n\nThe origin is used if 'to' is not given."
+        )
+    );
+    assert_eq!(
+        distance["params"],
+        json!([
+            {
+                "name": "from",
+                "required": true,
+                "schema": { "$ref": "#/components/schemas/Point" },
+            },
+            {
+                "name": "to",
+                "required": false,
+                "schema": {
+                    "anyOf": [{ "$ref": "#/components/schemas/Point" }, { "type": "null" }],
+                },
+            },
+        ])
+    );
+    assert_eq!(distance["result"]["schema"]["type"], json!("number"));
+
+    // Reserved parameters and application errors are not described.
+    let midpoint = &document["methods"][1];
+    assert_eq!(midpoint["name"], json!("geo_midpoint"));
+    assert_eq!(midpoint["params"].as_array().unwrap().len(), 2);
+    assert_eq!(
+        midpoint["result"]["schema"],
+        json!({ "$ref": "#/components/schemas/Point" })
+    );
+
+    assert_eq!(
+        document["methods"][2]["result"]["schema"]["type"],
+        json!("null")
+    );
+    // Subscriptions result in the id of the subscription.
+    assert_eq!(
+        document["methods"][3]["result"]["schema"]["type"],
+        json!("integer")
+    );
+    assert_eq!(
+        document["components"]["schemas"]["Point"]["required"],
+        json!(["x", "y"])
+    );
+}
+
+#[tokio::test]
+async fn discover() {
+    let result = Geometry::caller(
+        Arc::new(Geometry),
+        context(),
+        String::from("rpc.discover"),
+        json!(null),
+    )
+    .await
+    .unwrap();
+    let document: Document = serde_json::from_value(result).unwrap();
+    assert_eq!(document, Geometry::OPENRPC.document());
+
+    let result = MyShapes::caller(
+        Arc::new(MyShapes),
+        context(),
+        String::from("rpc.discover"),
+        json!(null),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result["info"]["title"], json!("Shapes"));
+    assert_eq!(result["methods"][0]["name"], json!("area"));
+    assert_eq!(
+        result["methods"][0]["description"],
+        json!("Area of a circle.")
+    );
+}
@@ -0,0 +1,92 @@
+use {
+    srpc::{
+        client::Client,
+        json_rpc::{Error, ErrorKind},
+        peer::Peer,
+        server::{Context, Server},
+        transport::Transport,
+    },
+    std::sync::Arc,
+    tokio::sync::mpsc,
+};
+
+mod common;
+
+#[srpc::client]
+trait Files {
+    async fn delete(path: String) -> bool;
+}
+
+#[srpc::client]
+trait Editor {
+    async fn confirm(question: String) -> bool;
+
+    #[notification]
+    async fn log(line: String);
+}
+
+struct FileServer;
+
+#[srpc::service]
+impl FileServer {
+    async fn delete(context: Arc<Context>, path: String) -> bool {
+        let editor = EditorClient::new(context.peer().unwrap().client());
+        editor.log(format!("deleting {}", path)).await.unwrap();
+        editor.confirm(format!("delete {}?", path)).await.unwrap()
+    }
+
+    // Returns whether the caller does not serve 'confirm'.
+    async fn ask(context: Arc<Context>) -> bool {
+        let editor = EditorClient::new(context.peer().unwrap().client());
+        match editor.confirm(String::from("sure?")).await {
+            Ok(_) => false,
+            Err(e) => matches!(
+                e.downcast_ref::<Error>().map(|error| &error.kind),
+                Some(ErrorKind::MethodNotFound)
+            ),
+        }
+    }
+}
+
+struct EditorServer {
+    logs: mpsc::UnboundedSender<String>,
+}
+
+#[srpc::service]
+impl EditorServer {
+    fn confirm(question: String) -> bool {
+        question == "delete cool.txt?"
+    }
+
+    fn log(&self, line: String) {
+        let _ = self.logs.send(line);
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn both_sides_call_each_other() {
+    let addr = common::serve(Server::new(FileServer, FileServer::caller)).await;
+
+    let (logs, mut received) = mpsc::unbounded_channel();
+    let server = Server::new(EditorServer { logs }, EditorServer::caller);
+    let peer = Peer::connect(addr, server).await.unwrap();
+    let files = FilesClient::new(peer.client());
+
+    assert!(files.delete(String::from("cool.txt")).await.unwrap());
+    assert!(!files.delete(String::from("lib.rs")).await.unwrap());
+    assert_eq!(received.recv().await.unwrap(), "deleting cool.txt");
+    assert_eq!(received.recv().await.unwrap(), "deleting lib.rs");
+}
+
+#[srpc::client]
+trait Asker {
+    async fn ask() -> bool;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn plain_client_responds_with_error() {
+    let addr = common::serve(Server::new(FileServer, FileServer::caller)).await;
+
+    let asker = AskerClient::new(Client::new(addr, Arc::new(Transport::new())));
+    assert!(asker.ask().await.unwrap());
+}
@@ -0,0 +1,229 @@
+use {
+    serde_json::json,
+    srpc::{json_rpc::ErrorKind, server::Context},
+    std::{marker::PhantomData, sync::Arc},
+};
+
+struct StrService {
+    prefix: String,
+}
+
+#[srpc::service]
+impl StrService {
+    async fn contains(data: String, elem: String) -> bool {
+        Self::find(data, elem).await.is_ok()
+    }
+
+    #[inline]
+    async fn find(data: String, elem: String) -> Result<usize, srpc::json_rpc::Error> {
+        data.find(&elem)
+            .ok_or_else(|| srpc::json_rpc::Error::custom(1, "Element not found", Some(json!(elem))))
+    }
+
+    async fn caller_port(context: Arc<Context>) -> u16 {
+        context.caller_addr.port()
+    }
+
+    fn len(data: String) -> usize {
+        data.len()
+    }
+
+    fn with_prefix(&self, data: String) -> String {
+        format!("{}{}", self.prefix, data)
+    }
+
+    async fn starts_with_prefix(&self, data: String) -> bool {
+        data.starts_with(&self.prefix)
+    }
+
+    #[blocking]
+    fn count(&self, data: String, elem: String) -> usize {
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        data.matches(&elem).count()
+    }
+}
+
+fn str_service() -> Arc<StrService> {
+    Arc::new(StrService {
+        prefix: String::from("cool "),
+    })
+}
+
+struct Store<T> {
+    data: PhantomData<T>,
+}
+
+#[srpc::service]
+impl<T> Store<T>
+where
+    T: Default + serde::Serialize + Send + Sync + 'static,
+{
+    async fn get(self: Arc<Self>) -> T {
+        T::default()
+    }
+}
+
+struct Eth;
+
+#[srpc::service(namespace = "eth")]
+impl Eth {
+    #[rpc(name = "getBalance", alias = "balance")]
+    fn get_balance(account: String) -> usize {
+        account.len()
+    }
+
+    fn block_number() -> u64 {
+        42
+    }
+}
+
+fn context() -> Arc<Context> {
+    Arc::new(Context::new(([127, 0, 0, 1], 8080).into()))
+}
+
+#[tokio::test]
+async fn methods_are_callable_directly() {
+    assert!(StrService::contains(String::from("cool lib"), String::from("lib")).await);
+    assert_eq!(
+        StrService::find(String::from("cool lib"), String::from("lib"))
+            .await
+            .unwrap(),
+        5
+    );
+}
+
+#[tokio::test]
+async fn dispatch() {
+    let service = str_service();
+
+    let result = StrService::caller(
+        service.clone(),
+        context(),
+        String::from("contains"),
+        json!({ "data": "cool lib", "elem": "lib" }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result, json!(true));
+
+    let result = StrService::caller(
+        service.clone(),
+        context(),
+        String::from("caller_port"),
+        json!(null),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result, json!(8080));
+
+    let error = StrService::caller(
+        service.clone(),
+        context(),
+        String::from("find"),
+        json!({ "data": "cool lib", "elem": "rpc" }),
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(error.kind, ErrorKind::Custom(1));
+    assert_eq!(error.data, Some(json!("rpc")));
+
+    let error = StrService::caller(service.clone(), context(), String::from("find"), json!({}))
+        .await
+        .unwrap_err();
+    assert_eq!(error.kind, ErrorKind::InvalidParams);
+
+    let error = StrService::caller(service, context(), String::from("unknown"), json!(null))
+        .await
+        .unwrap_err();
+    assert_eq!(error.kind, ErrorKind::MethodNotFound);
+}
+
+#[tokio::test]
+async fn dispatch_sync_and_ref_self_methods() {
+    let service = str_service();
+
+    let result = StrService::caller(
+        service.clone(),
+        context(),
+        String::from("len"),
+        json!({ "data": "lib" }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result, json!(3));
+
+    let result = StrService::caller(
+        service.clone(),
+        context(),
+        String::from("with_prefix"),
+        json!({ "data": "lib" }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result, json!("cool lib"));
+
+    let result = StrService::caller(
+        service,
+        context(),
+        String::from("starts_with_prefix"),
+        json!({ "data": "cool lib" }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result, json!(true));
+}
+
+#[tokio::test]
+async fn dispatch_blocking_method() {
+    let result = StrService::caller(
+        str_service(),
+        context(),
+        String::from("count"),
+        json!({ "data": "cool lib, cool rpc", "elem": "cool" }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result, json!(2));
+}
+
+#[tokio::test]
+async fn dispatch_generic_service() {
+    let store = Arc::new(Store::<u32> { data: PhantomData });
+    let result = Store::caller(store, context(), String::from("get"), json!(null))
+        .await
+        .unwrap();
+    assert_eq!(result, json!(0));
+}
+
+#[tokio::test]
+async fn dispatch_renamed_methods() {
+    let eth = Arc::new(Eth);
+    for name in &["eth_getBalance", "balance"] {
+        let result = Eth::caller(
+            eth.clone(),
+            context(),
+            String::from(*name),
+            json!({ "account": "0x42" }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(result, json!(4));
+    }
+
+    let result = Eth::caller(
+        eth.clone(),
+        context(),
+        String::from("eth_block_number"),
+        json!(null),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result, json!(42));
+
+    for name in &["get_balance", "eth_get_balance", "block_number"] {
+        let error = Eth::caller(eth.clone(), context(), String::from(*name), json!(null))
+            .await
+            .unwrap_err();
+        assert_eq!(error.kind, ErrorKind::MethodNotFound);
+    }
+}
@@ -0,0 +1,232 @@
+use {
+    futures::{stream, Stream, StreamExt},
+    srpc::{
+        client::Client,
+        json_rpc,
+        server::{Context, Server},
+        transport::Transport,
+    },
+    std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        time::Duration,
+    },
+    tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::{TcpListener, TcpStream},
+    },
+};
+
+mod common;
+
+#[srpc::client]
+trait Ticks {
+    #[subscription]
+    async fn ticks(count: u32) -> impl Stream<Item = u32>;
+
+    #[subscription]
+    async fn forever() -> impl Stream<Item = u64>;
+
+    #[subscription]
+    async fn checked(count: u32) -> impl Stream<Item = u32>;
+}
+
+/// Number of the 'forever' streams that are not dropped yet.
+static LIVE: AtomicUsize = AtomicUsize::new(0);
+
+struct Live;
+
+impl Live {
+    fn new() -> Self {
+        LIVE.fetch_add(1, Ordering::SeqCst);
+        Live
+    }
+}
+
+impl Drop for Live {
+    fn drop(&mut self) {
+        LIVE.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+struct TickServer;
+
+#[srpc::service]
+impl TickServer {
+    #[subscription]
+    fn ticks(count: u32) -> impl Stream<Item = u32> {
+        stream::iter(1..=count)
+    }
+
+    #[subscription]
+    fn forever(context: Arc<Context>) -> impl Stream<Item = u64> {
+        let _ = context.caller_addr;
+        stream::unfold((0, Live::new()), |(i, live)| async move {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            Some((i, (i + 1, live)))
+        })
+    }
+
+    #[subscription]
+    fn checked(count: u32) -> Result<impl Stream<Item = u32>, json_rpc::Error> {
+        if count == 0 {
+            Err(json_rpc::Error::custom(1, "Nothing to count", None))
+        } else {
+            Ok(stream::iter(0..count))
+        }
+    }
+}
+
+async fn wait_until_dropped() {
+    for _ in 0..100 {
+        if LIVE.load(Ordering::SeqCst) == 0 {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!("the stream is not dropped");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn subscriptions() {
+    let addr = common::serve(Server::new(TickServer, TickServer::caller)).await;
+
+    let client = Client::new(addr, Arc::new(Transport::new()));
+    let ticks = TicksClient::new(client);
+
+    // The stream ends when the server ends the subscription.
+    let items: Vec<_> = ticks.ticks(3).await.unwrap().collect().await;
+    let items: Vec<u32> = items.into_iter().map(Result::unwrap).collect();
+    assert_eq!(items, vec![1, 2, 3]);
+
+    let mut checked = ticks.checked(2).await.unwrap();
+    assert_eq!(checked.next().await.unwrap().unwrap(), 0);
+    assert!(ticks.checked(0).await.is_err());
+
+    // Dropping the subscription unsubscribes.
+    let mut forever = ticks.forever().await.unwrap();
+    assert_eq!(forever.next().await.unwrap().unwrap(), 0);
+    assert_eq!(forever.next().await.unwrap().unwrap(), 1);
+    assert_eq!(LIVE.load(Ordering::SeqCst), 1);
+    drop(forever);
+    wait_until_dropped().await;
+
+    let forever = ticks.forever().await.unwrap();
+    assert_eq!(LIVE.load(Ordering::SeqCst), 1);
+    forever.unsubscribe().await.unwrap();
+    wait_until_dropped().await;
+
+    // Subscriptions are stopped when the connection is closed.
+    let mut connection = TcpStream::connect(addr).await.unwrap();
+    let request = serde_json::to_vec(&json_rpc::Request::new(
+        String::from("forever"),
+        serde_json::Value::Null,
+        Some(json_rpc::Id::Num(1)),
+    ))
+    .unwrap();
+    connection
+        .write_all(&(request.len() as u32).to_le_bytes())
+        .await
+        .unwrap();
+    connection.write_all(&request).await.unwrap();
+    // The stream is created before the response is sent.
+    let mut length = [0; 4];
+    connection.read_exact(&mut length).await.unwrap();
+    connection
+        .read_exact(&mut vec![0; u32::from_le_bytes(length) as usize])
+        .await
+        .unwrap();
+    assert_eq!(LIVE.load(Ordering::SeqCst), 1);
+    drop(connection);
+    wait_until_dropped().await;
+}
+
+async fn read_message(connection: &mut TcpStream) -> serde_json::Value {
+    let mut length = [0; 4];
+    connection.read_exact(&mut length).await.unwrap();
+    let mut data = vec![0; u32::from_le_bytes(length) as usize];
+    connection.read_exact(&mut data).await.unwrap();
+    serde_json::from_slice(&data).unwrap()
+}
+
+async fn write_message(connection: &mut TcpStream, message: serde_json::Value) {
+    let data = serde_json::to_vec(&message).unwrap();
+    connection
+        .write_all(&(data.len() as u32).to_le_bytes())
+        .await
+        .unwrap();
+    connection.write_all(&data).await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn stream_ends_when_connection_closes() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let ticks = TicksClient::new(Client::new(
+        listener.local_addr().unwrap(),
+        Arc::new(Transport::new()),
+    ));
+
+    // The server answers the subscription, sends an item and goes away.
+    let server = tokio::spawn(async move {
+        let (mut connection, _) = listener.accept().await.unwrap();
+        let request = read_message(&mut connection).await;
+        let response = serde_json::json!({ "jsonrpc": "2.0", "result": 1, "id": request["id"] });
+        write_message(&mut connection, response).await;
+        let item = serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": json_rpc::subscription::SUBSCRIPTION,
+            "params": { "subscription": 1, "result": 7 },
+        });
+        write_message(&mut connection, item).await;
+    });
+
+    let mut ticks = ticks.ticks(3).await.unwrap();
+    assert_eq!(ticks.next().await.unwrap().unwrap(), 7);
+    server.await.unwrap();
+    assert!(ticks.next().await.is_none());
+}
+
+struct EventServer {
+    events: std::sync::Mutex<Option<futures::channel::mpsc::UnboundedReceiver<String>>>,
+}
+
+#[srpc::service]
+impl EventServer {
+    #[subscription]
+    fn events(&self) -> impl Stream<Item = String> {
+        self.events.lock().unwrap().take().unwrap()
+    }
+}
+
+#[srpc::client]
+trait Events {
+    #[subscription]
+    async fn events() -> impl Stream<Item = String>;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn same_ids_of_different_servers_do_not_mix() {
+    // Both servers give the id 1 to the first subscription of the connection.
+    let transport = Arc::new(Transport::new());
+    let mut senders = Vec::new();
+    let mut subscriptions = Vec::new();
+    for _ in 0..2 {
+        let (sender, events) = futures::channel::mpsc::unbounded();
+        let server = EventServer {
+            events: std::sync::Mutex::new(Some(events)),
+        };
+        let addr = common::serve(Server::new(server, EventServer::caller)).await;
+        let client = EventsClient::new(Client::new(addr, transport.clone()));
+        let subscription = client.events().await.unwrap();
+        assert_eq!(subscription.id(), 1);
+        senders.push(sender);
+        subscriptions.push(subscription);
+    }
+
+    senders[0].unbounded_send(String::from("first")).unwrap();
+    senders[1].unbounded_send(String::from("second")).unwrap();
+    assert_eq!(subscriptions[0].next().await.unwrap().unwrap(), "first");
+    assert_eq!(subscriptions[1].next().await.unwrap().unwrap(), "second");
+}
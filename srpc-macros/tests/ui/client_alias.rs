@@ -0,0 +1,7 @@
+#[srpc::client(namespace = "str")]
+trait StrService {
+    #[rpc(name = "length", alias = "len")]
+    async fn len(data: String) -> usize;
+}
+
+fn main() {}
@@ -0,0 +1,6 @@
+#[srpc::client(unknown)]
+trait StrService {
+    async fn contains(data: String, elem: String) -> bool;
+}
+
+fn main() {}
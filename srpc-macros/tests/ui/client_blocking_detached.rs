@@ -0,0 +1,10 @@
+#[srpc::client]
+trait StrService {
+    #[blocking]
+    async fn contains(data: String, elem: String) -> bool;
+
+    #[detached]
+    async fn save(data: String);
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+#[srpc::client]
+trait StrService {
+    type Data;
+
+    async fn contains(&mut self, data: String, elem: String) -> bool;
+
+    #[notification]
+    async fn set_data(is_cool: bool) -> bool;
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+#[srpc::client]
+trait StrService {
+    const LIMIT: usize;
+
+    async fn contains(data: String, elem: String) -> bool;
+}
+
+fn main() {}
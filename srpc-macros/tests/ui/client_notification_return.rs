@@ -0,0 +1,7 @@
+#[srpc::client]
+trait StrService {
+    #[notification]
+    async fn set_data(is_cool: bool) -> bool;
+}
+
+fn main() {}
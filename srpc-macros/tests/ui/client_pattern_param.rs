@@ -0,0 +1,6 @@
+#[srpc::client]
+trait StrService {
+    async fn contains((data, elem): (String, String)) -> bool;
+}
+
+fn main() {}
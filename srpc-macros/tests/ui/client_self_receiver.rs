@@ -0,0 +1,6 @@
+#[srpc::client]
+trait StrService {
+    async fn contains(&mut self, data: String, elem: String) -> bool;
+}
+
+fn main() {}
@@ -0,0 +1,7 @@
+#[srpc::client]
+trait Ticks {
+    #[subscription]
+    async fn ticks(count: u32) -> Vec<u32>;
+}
+
+fn main() {}
@@ -0,0 +1,6 @@
+// trybuild builds the cases in 'target/tests/trybuild/srpc-macros'.
+mod api {
+    srpc::include_client!("../../../../srpc-macros/tests/ui/api/no_methods.json");
+}
+
+fn main() {}
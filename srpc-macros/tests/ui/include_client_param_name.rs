@@ -0,0 +1,6 @@
+// trybuild builds the cases in 'target/tests/trybuild/srpc-macros'.
+mod api {
+    srpc::include_client!("../../../../srpc-macros/tests/ui/api/param_name.json");
+}
+
+fn main() {}
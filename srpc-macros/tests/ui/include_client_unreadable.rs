@@ -0,0 +1,5 @@
+mod api {
+    srpc::include_client!("tests/ui/api/missing.json");
+}
+
+fn main() {}
@@ -0,0 +1,14 @@
+#[srpc::interface]
+trait StrService {
+    async fn contains(&self, data: String, elem: String) -> bool;
+}
+
+struct MyService;
+
+impl StrService for MyService {
+    async fn contains(&self, data: String, elem: u32) -> bool {
+        data.len() as u32 == elem
+    }
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+struct StrService;
+
+#[srpc::service(unknown)]
+impl StrService {
+    async fn contains(data: String, elem: String) -> bool {
+        data.contains(&elem)
+    }
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+struct StrService;
+
+#[srpc::service]
+impl StrService {
+    #[blocking]
+    async fn contains(data: String, elem: String) -> bool {
+        data.contains(&elem)
+    }
+}
+
+fn main() {}
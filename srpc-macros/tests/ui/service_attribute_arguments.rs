@@ -0,0 +1,13 @@
+struct StrService;
+
+#[srpc::service]
+impl StrService {
+    #[rpc(unknown)]
+    #[blocking(threads = 2)]
+    #[detached(always)]
+    fn contains(data: String, elem: String) -> bool {
+        data.contains(&elem)
+    }
+}
+
+fn main() {}
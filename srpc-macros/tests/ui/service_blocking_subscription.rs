@@ -0,0 +1,12 @@
+struct Ticks;
+
+#[srpc::service]
+impl Ticks {
+    #[blocking]
+    #[subscription]
+    fn ticks(count: u32) -> Vec<u32> {
+        (0..count).collect()
+    }
+}
+
+fn main() {}
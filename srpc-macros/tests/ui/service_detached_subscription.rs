@@ -0,0 +1,12 @@
+struct Ticks;
+
+#[srpc::service]
+impl Ticks {
+    #[detached]
+    #[subscription]
+    fn ticks(count: u32) -> impl futures::Stream<Item = u32> {
+        futures::stream::iter(0..count)
+    }
+}
+
+fn main() {}
@@ -0,0 +1,19 @@
+struct StrService;
+
+#[srpc::service]
+impl StrService {
+    #[rpc(name = "length")]
+    fn len(data: String) -> usize {
+        data.len()
+    }
+
+    #[rpc(alias = "length")]
+    fn size(data: String) -> usize {
+        data.len()
+    }
+
+    #[rpc(name = "rpc.discover")]
+    fn discover() {}
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+struct StrService;
+
+#[srpc::service]
+impl StrService {
+    const LIMIT: usize = 10;
+
+    async fn contains(data: String, elem: String) -> bool {
+        data.contains(&elem)
+    }
+}
+
+fn main() {}
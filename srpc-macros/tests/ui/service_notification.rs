@@ -0,0 +1,9 @@
+struct StrServer;
+
+#[srpc::service]
+impl StrServer {
+    #[notification]
+    fn set_data(is_cool: bool) {}
+}
+
+fn main() {}
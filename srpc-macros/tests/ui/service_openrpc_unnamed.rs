@@ -0,0 +1,8 @@
+#[srpc::service(openrpc)]
+impl (u8, u8) {
+    fn sum(a: u8, b: u8) -> u8 {
+        a + b
+    }
+}
+
+fn main() {}
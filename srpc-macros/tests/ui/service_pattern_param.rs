@@ -0,0 +1,10 @@
+struct StrService;
+
+#[srpc::service]
+impl StrService {
+    async fn contains((data, elem): (String, String)) -> bool {
+        data.contains(&elem)
+    }
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+struct StrService;
+
+#[srpc::service]
+impl StrService {
+    async fn contains(&mut self, data: String, elem: String) -> bool {
+        data.contains(&elem)
+    }
+}
+
+fn main() {}
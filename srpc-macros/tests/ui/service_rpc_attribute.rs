@@ -0,0 +1,16 @@
+struct StrService;
+
+#[srpc::service]
+impl StrService {
+    #[rpc(name = length)]
+    fn count(data: String) -> usize {
+        data.len()
+    }
+
+    #[rpc(title = "count")]
+    fn chars(data: String) -> usize {
+        data.chars().count()
+    }
+}
+
+fn main() {}
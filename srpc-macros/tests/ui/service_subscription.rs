@@ -0,0 +1,9 @@
+struct Ticks;
+
+#[srpc::service]
+impl Ticks {
+    #[subscription]
+    fn ticks(count: u32) {}
+}
+
+fn main() {}
@@ -0,0 +1,73 @@
+use {
+    schemars::JsonSchema,
+    serde::Deserialize,
+    serde_json::json,
+    srpc::{
+        client::Client,
+        json_rpc::{ErrorKind, Request},
+        openrpc::validation::Validator,
+        server::Server,
+        transport::Transport,
+    },
+    std::sync::Arc,
+};
+
+mod common;
+
+#[derive(Deserialize, JsonSchema)]
+struct User {
+    #[schemars(length(min = 1))]
+    name: String,
+    #[schemars(range(max = 150))]
+    age: u8,
+    #[schemars(regex(pattern = r"^[^@]+@[^@]+$"))]
+    email: String,
+}
+
+struct Users;
+
+#[srpc::service(openrpc)]
+impl Users {
+    fn register(user: User, admin: bool) -> String {
+        let role = if admin { "admin" } else { "user" };
+        format!("{} ({}, {}) <{}>", user.name, user.age, role, user.email)
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn params_are_validated() {
+    let mut server = Server::new(Users, Users::caller);
+    server.set_validator(Validator::new(&Users::OPENRPC).unwrap());
+    let addr = common::serve(server).await;
+
+    let client = Client::new(addr, Arc::new(Transport::new()));
+    let register = |params| Request::new(String::from("register"), params, None);
+
+    let response = client
+        .call(register(json!({
+            "user": { "name": "cool", "age": 3, "email": "cool@lib" },
+            "admin": false,
+        })))
+        .await
+        .unwrap();
+    assert_eq!(response.result, Some(json!("cool (3, user) <cool@lib>")));
+
+    let response = client
+        .call(register(json!({
+            "user": { "name": "", "age": 200, "email": "cool" },
+        })))
+        .await
+        .unwrap();
+    let error = response.error.unwrap();
+    assert_eq!(error.kind, ErrorKind::InvalidParams);
+
+    let violations = error.data.unwrap()["violations"].clone();
+    let mut paths: Vec<_> = violations
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|violation| violation["path"].as_str().unwrap().to_string())
+        .collect();
+    paths.sort();
+    assert_eq!(paths, vec!["", "/user/age", "/user/email", "/user/name"]);
+}
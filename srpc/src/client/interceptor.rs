@@ -0,0 +1,80 @@
+//! Interceptors of the client.
+//!
+//! An [Interceptor] wraps every request that the [Client](super::Client) sends, including the
+//! requests of the stubs generated by `#[srpc::client]`. It gets the request before it is sent
+//! and passes it to the rest of the chain with [Next::run], so it can change the request, fail
+//! without sending it, send it again or inspect the response. Interceptors run in the order
+//! they are added to the client, the first one being the outermost.
+//!
+//! Ids are assigned before the interceptors run, and notifications don't have one. The chain
+//! returns `None` for notifications since they don't get a response.
+//!
+//! # Example
+//! ```no_run
+//! use srpc::client::interceptor::{Interceptor, Next};
+//!
+//! struct Token(String);
+//!
+//! impl Interceptor for Token {
+//!     fn intercept<'a>(
+//!         &'a self,
+//!         mut request: json_rpc::Request,
+//!         next: Next<'a>,
+//!     ) -> BoxFuture<'a, srpc::Result<Option<json_rpc::Response>>> {
+//!         Box::pin(async move {
+//!             request.params["token"] = serde_json::json!(self.0);
+//!             next.run(request).await
+//!         })
+//!     }
+//! }
+//!
+//! let mut client = Client::new(([127, 0, 0, 1], 8080).into(), transporter);
+//! client.add_interceptor(Token(String::from("secret")));
+//! let service = StrServiceClient::new(client);
+//! ```
+use {
+    super::Client,
+    crate::{json_rpc, BoxFuture},
+    std::sync::Arc,
+};
+
+pub trait Interceptor: Send + Sync + 'static {
+    /// Intercepts the request, usually by passing it to `next`.
+    fn intercept<'a>(
+        &'a self,
+        request: json_rpc::Request,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, crate::Result<Option<json_rpc::Response>>>;
+}
+
+/// The rest of the interceptor chain, which ends with sending the request. It can be cloned to
+/// send the request more than once.
+#[derive(Clone, Copy)]
+pub struct Next<'a> {
+    interceptors: &'a [Arc<dyn Interceptor>],
+    client: &'a Client,
+}
+
+impl<'a> Next<'a> {
+    pub(crate) fn new(interceptors: &'a [Arc<dyn Interceptor>], client: &'a Client) -> Self {
+        Self {
+            interceptors,
+            client,
+        }
+    }
+
+    /// Passes the request to the next interceptor, or sends it if this is the end of the chain.
+    pub async fn run(
+        self,
+        request: json_rpc::Request,
+    ) -> crate::Result<Option<json_rpc::Response>> {
+        match self.interceptors.split_first() {
+            Some((interceptor, rest)) => {
+                interceptor
+                    .intercept(request, Next::new(rest, self.client))
+                    .await
+            }
+            None => self.client.send(request).await,
+        }
+    }
+}
@@ -0,0 +1,376 @@
+//! The async RPC client.
+//!
+//! The [Client]() connects to [Server](crate::server) and unless a connection error occurs, it does not
+//! drop the connection. Timeouts are not supported yet.
+//!
+//! ```no_run
+//! use {
+//!   srpc::{client::Client, transport::Transport},
+//!   std::sync::Arc,
+//! };
+//!
+//! #[srpc::client]
+//! trait StrService {
+//!    async fn contains(data: String, elem: String) -> bool;
+//!
+//!    #[notification]
+//!    async fn set_data(is_cool: bool);
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!    env_logger::init();
+//!    let transporter = Arc::new(Transport::new());
+//!    let client = Client::new(([127, 0, 0, 1], 8080).into(), transporter.clone());
+//!    let service = StrServiceClient::new(client);
+//!
+//!    for i in 0..100 {
+//!        let _ = service.set_data(i % 2 == 0).await;
+//!        println!(
+//!            "{}",
+//!            service
+//!                .contains(String::from("cool lib"), String::from("lib"))
+//!                .await
+//!                .unwrap()
+//!        );
+//!    }
+//! }
+//! ```
+//!
+//! `#[srpc::client]` generates `StrServiceClient`, which wraps a [Client] and implements the
+//! `StrService` trait. Code that is generic over `StrService` can be tested with a test double
+//! instead of a real connection. A [Client] is cheap to clone and the clones share the same
+//! connection, so several stubs can be created from one client.
+//!
+//! Credentials, trace ids or logging can be added to every request with
+//! [interceptors](interceptor).
+//!
+//! Data like auth tokens or trace ids can be sent in the `meta` member of the request instead
+//! of the params. [Client::with_meta] adds a member to every request of the client, and
+//! interceptors can set `request.meta` too. The server puts it in the `Context` of the call.
+//! `meta` is not a part of JSON-RPC, so [Client::set_send_meta] turns it off for servers that
+//! reject unknown members.
+//! ```no_run
+//! let client = Client::new(([127, 0, 0, 1], 8080).into(), transporter)
+//!     .with_meta("tenant", "cool-lib");
+//! ```
+//!
+//! Methods marked with `#[subscription]` return a [Subscription], which is a stream of the
+//! items that the server sends. Dropping the subscription unsubscribes.
+//! ```no_run
+//! #[srpc::client]
+//! trait Chain {
+//!     #[subscription]
+//!     async fn new_heads(&self) -> impl Stream<Item = Header>;
+//! }
+//!
+//! let mut heads = chain.new_heads().await?;
+//! while let Some(header) = heads.next().await {
+//!     println!("{:?}", header?);
+//! }
+//! ```
+//!
+//! The server can send notifications to the client over the same connection. They are passed
+//! to the handlers of their methods, and notifications without a handler are ignored.
+//! ```no_run
+//! client.on_notification("progress", |params| println!("{}", params["percent"]));
+//!
+//! let mut logs = client.notifications("log");
+//! while let Some(params) = logs.recv().await {
+//!     println!("{}", params);
+//! }
+//! ```
+//!
+//! A call is cancelled when its future is dropped before the response comes, e.g. by a timeout
+//! or by `futures::future::abortable`. The client sends a `$/cancelRequest` notification, and
+//! the server aborts the call without sending a response.
+//! ```no_run
+//! let hash = tokio::time::timeout(Duration::from_secs(1), service.hash(data)).await;
+//! ```
+//!
+
+pub mod interceptor;
+mod subscription;
+
+pub use subscription::Subscription;
+
+use {
+    super::{json_rpc, transport::*},
+    interceptor::{Interceptor, Next},
+    json_rpc::cancel::{CancelParams, CANCEL_REQUEST},
+    serde::de::DeserializeOwned,
+    serde_json::{Map, Value},
+    std::{net::SocketAddr, sync::Arc},
+    tokio::{
+        io,
+        net::TcpStream,
+        sync::{mpsc, oneshot, Mutex},
+    },
+};
+
+#[derive(Clone)]
+pub struct Client {
+    connection: Arc<Mutex<Option<Connection>>>,
+    service_addr: SocketAddr,
+    transporter: Arc<Transport>,
+    interceptors: Vec<Arc<dyn Interceptor>>,
+    // Added to the meta of every request
+    meta: Map<String, Value>,
+    send_meta: bool,
+}
+
+impl Client {
+    pub fn new(service_addr: SocketAddr, transporter: Arc<Transport>) -> Self {
+        Self {
+            connection: Arc::new(Mutex::new(None)),
+            service_addr,
+            transporter,
+            interceptors: Vec::new(),
+            meta: Map::new(),
+            send_meta: true,
+        }
+    }
+
+    /// Creates a client over a connection which is already open, e.g. the connection of a
+    /// [Peer](crate::peer::Peer). The client does not reconnect when the connection closes.
+    /// `connection` is the id of the connection in the transport.
+    pub(crate) fn connected(
+        service_addr: SocketAddr,
+        connection: u64,
+        sender: mpsc::UnboundedSender<Vec<u8>>,
+        transporter: Arc<Transport>,
+    ) -> Self {
+        let connection = Connection {
+            id: connection,
+            sender,
+        };
+        Self {
+            connection: Arc::new(Mutex::new(Some(connection))),
+            ..Self::new(service_addr, transporter)
+        }
+    }
+
+    /// Adds a member to the meta of every request. Members that the request already has are not
+    /// overwritten.
+    pub fn with_meta<K: Into<String>, V: Into<Value>>(mut self, key: K, value: V) -> Self {
+        self.meta.insert(key.into(), value.into());
+        self
+    }
+
+    /// Registers a handler which is called with the params of the notifications of the method
+    /// that the server sends. Handlers are shared by the clients of the same transport.
+    pub fn on_notification<F: Fn(Value) + Send + 'static>(&self, method: &str, handler: F) {
+        self.transporter
+            .add_notification_handler(String::from(method), move |notification| {
+                handler(notification.params.clone());
+                true
+            });
+    }
+
+    /// Returns the params of the notifications of the method that the server sends. The
+    /// receiver stops getting notifications when it is dropped.
+    pub fn notifications(&self, method: &str) -> mpsc::UnboundedReceiver<Value> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        self.transporter
+            .add_notification_handler(String::from(method), move |notification| {
+                tx.send(notification.params.clone()).is_ok()
+            });
+        rx
+    }
+
+    /// Sets whether the meta of the requests is sent. It is sent by default.
+    pub fn set_send_meta(&mut self, send_meta: bool) {
+        self.send_meta = send_meta;
+    }
+
+    /// Adds an interceptor which wraps every request. Interceptors run in the order they are
+    /// added. Clones of the client that are made before keep their interceptors.
+    pub fn add_interceptor<I: Interceptor>(&mut self, interceptor: I) {
+        self.interceptors.push(Arc::new(interceptor));
+    }
+
+    /// Provides a persistent connection.
+    pub async fn handle_connection(&self) -> crate::Result<()> {
+        self.connection().await.map(|_| ())
+    }
+
+    /// Returns the connection, and connects if it is not open yet.
+    async fn connection(&self) -> crate::Result<Connection> {
+        let mut connection = self.connection.lock().await;
+        // Do nothing if there is already an open connection
+        if let Some(connection) = connection.as_ref() {
+            return Ok(connection.clone());
+        }
+
+        let stream = TcpStream::connect(self.service_addr).await?;
+        let (read_half, write_half) = io::split(stream);
+        let writer = self.transporter.spawn_writer(write_half);
+        let id = self.transporter.spawn_reader(read_half, writer.clone());
+        // Save the sender coming from the writer
+        let open = Connection { id, sender: writer };
+        *connection = Some(open.clone());
+
+        Ok(open)
+    }
+
+    pub fn create_data(&self, request: &json_rpc::Request) -> crate::Result<Vec<u8>> {
+        let data_to_send = serde_json::to_vec(&request).unwrap();
+        // To support 32 bit machines easily
+        if data_to_send.len() > std::u32::MAX as usize {
+            Err(format!("max data size ({}) is exceeded.", std::u32::MAX).into())
+        } else {
+            Ok(data_to_send)
+        }
+    }
+
+    /// Makes an rpc call and waits for the response
+    pub async fn call(&self, mut request: json_rpc::Request) -> crate::Result<json_rpc::Response> {
+        request.id = Some(json_rpc::Id::Num(rand::random::<u32>()));
+        self.add_meta(&mut request);
+
+        Next::new(&self.interceptors, self)
+            .run(request)
+            .await?
+            .ok_or_else(|| String::from("an interceptor returned no response to a call").into())
+    }
+
+    /// Makes a subscription call and returns the items of the subscription as a stream.
+    pub async fn subscribe<T: DeserializeOwned>(
+        &self,
+        mut request: json_rpc::Request,
+    ) -> crate::Result<Subscription<T>> {
+        let id = json_rpc::Id::Num(rand::random::<u32>());
+        request.id = Some(id.clone());
+        self.add_meta(&mut request);
+
+        // Items are sent to the subscription as soon as the response comes.
+        let connection = self.connection().await?.id;
+        let (items, receiver) = mpsc::unbounded_channel();
+        self.transporter
+            .add_subscription(connection, id.clone(), items);
+        let response = match Next::new(&self.interceptors, self).run(request).await {
+            Ok(Some(response)) => response,
+            Ok(None) => {
+                self.transporter
+                    .remove_pending_subscription(connection, &id);
+                return Err("an interceptor returned no response to a call".into());
+            }
+            Err(e) => {
+                self.transporter
+                    .remove_pending_subscription(connection, &id);
+                return Err(e);
+            }
+        };
+
+        match response.error {
+            Some(error) => Err(error.into()),
+            None => {
+                let subscription_id =
+                    serde_json::from_value(response.result.unwrap_or(Value::Null))?;
+                Ok(Subscription::new(
+                    subscription_id,
+                    connection,
+                    receiver,
+                    self.clone(),
+                ))
+            }
+        }
+    }
+
+    /// Makes an rpc notification call and DOES NOT wait for the response
+    pub async fn notify(&self, mut request: json_rpc::Request) -> crate::Result<()> {
+        self.add_meta(&mut request);
+
+        Next::new(&self.interceptors, self).run(request).await?;
+        Ok(())
+    }
+
+    fn add_meta(&self, request: &mut json_rpc::Request) {
+        if self.meta.is_empty() {
+            return;
+        }
+        let meta = request.meta.get_or_insert_with(Map::new);
+        for (key, value) in &self.meta {
+            meta.entry(key.clone()).or_insert_with(|| value.clone());
+        }
+    }
+
+    /// Sends the request, and waits for the response if it is not a notification.
+    async fn send(
+        &self,
+        mut request: json_rpc::Request,
+    ) -> crate::Result<Option<json_rpc::Response>> {
+        let connection = self.connection().await?;
+
+        if !self.send_meta {
+            request.meta = None;
+        }
+
+        let data = self.create_data(&request)?;
+
+        let rx = request.id.clone().map(|id| {
+            let (tx, rx) = oneshot::channel::<json_rpc::Response>();
+            // Register to the receivers to receive the correct response
+            self.transporter.clone().add_receiver(connection.id, id, tx);
+            rx
+        });
+
+        connection.sender.send(data)?;
+
+        match rx {
+            Some(rx) => {
+                let mut guard = CancelOnDrop {
+                    client: self,
+                    connection: connection.id,
+                    id: request.id,
+                };
+                let response = rx.await?;
+                guard.id = None;
+                Ok(Some(response))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+/// The open connection of a client.
+#[derive(Clone)]
+struct Connection {
+    // Id of the connection in the transport
+    id: u64,
+    // Sends data to writer
+    sender: mpsc::UnboundedSender<Vec<u8>>,
+}
+
+/// Cancels the call if it is dropped before its response comes, e.g. when it times out.
+struct CancelOnDrop<'a> {
+    client: &'a Client,
+    connection: u64,
+    id: Option<json_rpc::Id>,
+}
+
+impl Drop for CancelOnDrop<'_> {
+    fn drop(&mut self) {
+        let id = match self.id.take() {
+            Some(id) => id,
+            None => return,
+        };
+        let transporter = &self.client.transporter;
+        transporter.remove_receiver(self.connection, &id);
+        transporter.remove_pending_subscription(self.connection, &id);
+        // The server runs the call to the end if there is no runtime to send the notification.
+        if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+            let client = self.client.clone();
+            let request = json_rpc::Request::new(
+                String::from(CANCEL_REQUEST),
+                serde_json::to_value(CancelParams { id }).unwrap(),
+                None,
+            );
+            runtime.spawn(async move {
+                if let Err(e) = client.notify(request).await {
+                    log::warn!("Failed to cancel the call: {}", e);
+                }
+            });
+        }
+    }
+}
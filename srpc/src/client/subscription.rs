@@ -0,0 +1,110 @@
+use {
+    super::Client,
+    crate::json_rpc::{self, subscription::*},
+    futures::Stream,
+    serde::de::DeserializeOwned,
+    serde_json::Value,
+    std::{
+        marker::PhantomData,
+        pin::Pin,
+        task::{Context, Poll},
+    },
+    tokio::sync::mpsc,
+};
+
+/// Items of a subscription, which the server sends as notifications. The stream ends when the
+/// server ends the subscription or the connection is closed. Dropping it unsubscribes.
+pub struct Subscription<T> {
+    id: u64,
+    // Id of the connection in the transport, subscription ids are unique per connection
+    connection: u64,
+    items: mpsc::UnboundedReceiver<Value>,
+    client: Client,
+    // Set when the server ends the subscription, so there is no need to unsubscribe
+    ended: bool,
+    item: PhantomData<fn() -> T>,
+}
+
+impl<T> Subscription<T> {
+    pub(super) fn new(
+        id: u64,
+        connection: u64,
+        items: mpsc::UnboundedReceiver<Value>,
+        client: Client,
+    ) -> Self {
+        Self {
+            id,
+            connection,
+            items,
+            client,
+            ended: false,
+            item: PhantomData,
+        }
+    }
+
+    /// Id of the subscription in the server.
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Ends the subscription and waits until the server is notified.
+    pub async fn unsubscribe(mut self) -> crate::Result<()> {
+        self.ended = true;
+        self.client
+            .transporter
+            .remove_subscription(self.connection, self.id);
+        self.client.notify(unsubscribe_request(self.id)).await
+    }
+}
+
+impl<T: DeserializeOwned> Stream for Subscription<T> {
+    type Item = crate::Result<T>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        match this.items.poll_recv(cx) {
+            Poll::Ready(Some(item)) => {
+                Poll::Ready(Some(serde_json::from_value(item).map_err(Into::into)))
+            }
+            Poll::Ready(None) => {
+                this.ended = true;
+                Poll::Ready(None)
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+impl<T> Drop for Subscription<T> {
+    fn drop(&mut self) {
+        if self.ended {
+            return;
+        }
+        self.client
+            .transporter
+            .remove_subscription(self.connection, self.id);
+        // The server stops sending the items when it gets the notification. If there is no
+        // runtime, the connection is about to be closed anyway.
+        if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+            let client = self.client.clone();
+            let request = unsubscribe_request(self.id);
+            runtime.spawn(async move {
+                if let Err(e) = client.notify(request).await {
+                    log::warn!("Failed to unsubscribe: {}", e);
+                }
+            });
+        }
+    }
+}
+
+fn unsubscribe_request(id: u64) -> json_rpc::Request {
+    let params = SubscriptionParams {
+        subscription: id,
+        result: None,
+    };
+    json_rpc::Request::new(
+        String::from(UNSUBSCRIBE),
+        serde_json::to_value(params).unwrap(),
+        None,
+    )
+}
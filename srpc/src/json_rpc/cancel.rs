@@ -0,0 +1,18 @@
+//! Notification of the cancelled calls, as in the Language Server Protocol. It is not a part of
+//! JSON-RPC, so its method name starts with `$/`.
+
+use {
+    super::Id,
+    serde::{Deserialize, Serialize},
+};
+
+/// Method of the notification which the caller sends when it does not wait for the response of
+/// a call anymore.
+pub const CANCEL_REQUEST: &str = "$/cancelRequest";
+
+/// Params of the cancel notification.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CancelParams {
+    /// Id of the cancelled request
+    pub id: Id,
+}
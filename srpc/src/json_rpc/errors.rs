@@ -3,7 +3,7 @@ use {
     serde_json::Value,
 };
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
 pub enum ErrorKind {
     ParseError,
     InvalidRequest,
@@ -11,24 +11,74 @@ pub enum ErrorKind {
     InvalidParams,
     InternalError,
     ServerError(u32),
+    /// Error defined by the application, see [ApplicationError].
+    Custom(i32),
 }
 
-// TODO: Custom (de)serialization is needed.
-// Error should be serialized to:
-// {
-//    "code": -32700,
-//    "message": "Parse error",
-//    "data": { .. }
-// }
-#[derive(Debug, Deserialize, Serialize)]
+/// A JSON-RPC error object. It is serialized to:
+/// ```text
+/// {
+///    "code": -32700,
+///    "message": "Parse error",
+///    "data": { .. }
+/// }
+/// ```
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(from = "RawError", into = "RawError")]
 pub struct Error {
     pub kind: ErrorKind,
+    pub message: String,
     pub data: Option<Value>,
 }
 
+#[derive(Deserialize, Serialize)]
+struct RawError {
+    code: i32,
+    message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    data: Option<Value>,
+}
+
 impl Error {
     pub fn new(kind: ErrorKind, data: Option<Value>) -> Self {
-        Self { kind, data }
+        Self {
+            kind,
+            message: String::from(kind.message()),
+            data,
+        }
+    }
+
+    /// Creates an application defined error with a custom code and message.
+    pub fn custom<M: Into<String>>(code: i32, message: M, data: Option<Value>) -> Self {
+        Self {
+            kind: ErrorKind::from_code(code),
+            message: message.into(),
+            data,
+        }
+    }
+
+    pub fn code(&self) -> i32 {
+        self.kind.code()
+    }
+}
+
+impl From<RawError> for Error {
+    fn from(raw: RawError) -> Self {
+        Self {
+            kind: ErrorKind::from_code(raw.code),
+            message: raw.message,
+            data: raw.data,
+        }
+    }
+}
+
+impl From<Error> for RawError {
+    fn from(error: Error) -> Self {
+        Self {
+            code: error.kind.code(),
+            message: error.message,
+            data: error.data,
+        }
     }
 }
 
@@ -36,11 +86,24 @@ impl ErrorKind {
     pub fn code(&self) -> i32 {
         match *self {
             ErrorKind::ParseError => -32700,
-            ErrorKind::InvalidRequest => -32700,
-            ErrorKind::MethodNotFound => -32700,
-            ErrorKind::InvalidParams => -32700,
-            ErrorKind::InternalError => -32700,
-            ErrorKind::ServerError(n) => -32700 - n as i32,
+            ErrorKind::InvalidRequest => -32600,
+            ErrorKind::MethodNotFound => -32601,
+            ErrorKind::InvalidParams => -32602,
+            ErrorKind::InternalError => -32603,
+            ErrorKind::ServerError(n) => -32000 - n as i32,
+            ErrorKind::Custom(code) => code,
+        }
+    }
+
+    pub fn from_code(code: i32) -> Self {
+        match code {
+            -32700 => ErrorKind::ParseError,
+            -32600 => ErrorKind::InvalidRequest,
+            -32601 => ErrorKind::MethodNotFound,
+            -32602 => ErrorKind::InvalidParams,
+            -32603 => ErrorKind::InternalError,
+            -32099..=-32000 => ErrorKind::ServerError((-32000 - code) as u32),
+            code => ErrorKind::Custom(code),
         }
     }
 
@@ -52,14 +115,117 @@ impl ErrorKind {
             ErrorKind::InvalidParams => "Invalid params",
             ErrorKind::InternalError => "Internal error",
             ErrorKind::ServerError(_) => "Server error",
+            ErrorKind::Custom(_) => "Application error",
         }
     }
 }
 
 impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Rpc error!")
+        write!(f, "Rpc error {}: {}", self.code(), self.message)
     }
 }
 
 impl std::error::Error for Error {}
+
+/// Errors that RPC methods return to their callers.
+///
+/// A service method declared as `-> Result<T, E>` sends `E` back as a JSON-RPC error object,
+/// and a client method declared with the same return type decodes it back into `E`.
+///
+/// ```no_run
+/// use srpc::json_rpc::{ApplicationError, Error, ErrorKind};
+///
+/// #[derive(serde::Serialize, serde::Deserialize)]
+/// struct NotFound {
+///     key: String,
+/// }
+///
+/// impl ApplicationError for NotFound {
+///     fn into_error(self) -> Error {
+///         Error::custom(404, "Not found", serde_json::to_value(self).ok())
+///     }
+///
+///     fn from_error(error: Error) -> Result<Self, Error> {
+///         match (error.kind, &error.data) {
+///             (ErrorKind::Custom(404), Some(data)) => {
+///                 serde_json::from_value(data.clone()).map_err(|_| error)
+///             }
+///             _ => Err(error),
+///         }
+///     }
+/// }
+/// ```
+pub trait ApplicationError: Sized {
+    /// Converts the error into a JSON-RPC error object.
+    fn into_error(self) -> Error;
+
+    /// Decodes the error from a JSON-RPC error object. The error object is given back if it
+    /// does not represent this type.
+    fn from_error(error: Error) -> Result<Self, Error>;
+}
+
+impl ApplicationError for Error {
+    fn into_error(self) -> Error {
+        self
+    }
+
+    fn from_error(error: Error) -> Result<Self, Error> {
+        Ok(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serialize_error_object() {
+        let error = Error::new(ErrorKind::InvalidParams, Some(Value::from("missing field")));
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            serde_json::json!({
+                "code": -32602,
+                "message": "Invalid params",
+                "data": "missing field",
+            })
+        );
+
+        let error = Error::new(ErrorKind::MethodNotFound, None);
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            serde_json::json!({ "code": -32601, "message": "Method not found" })
+        );
+    }
+
+    #[test]
+    fn deserialize_error_object() {
+        let error: Error = serde_json::from_value(serde_json::json!({
+            "code": 42,
+            "message": "Out of stock",
+            "data": { "item": "apple" },
+        }))
+        .unwrap();
+        assert_eq!(error.kind, ErrorKind::Custom(42));
+        assert_eq!(error.message, "Out of stock");
+        assert_eq!(error.data, Some(serde_json::json!({ "item": "apple" })));
+    }
+
+    #[test]
+    fn code_round_trip() {
+        let kinds = [
+            ErrorKind::ParseError,
+            ErrorKind::InvalidRequest,
+            ErrorKind::MethodNotFound,
+            ErrorKind::InvalidParams,
+            ErrorKind::InternalError,
+            ErrorKind::ServerError(0),
+            ErrorKind::ServerError(99),
+            ErrorKind::Custom(-1),
+            ErrorKind::Custom(404),
+        ];
+        for kind in kinds.iter() {
+            assert_eq!(ErrorKind::from_code(kind.code()), *kind);
+        }
+    }
+}
@@ -0,0 +1,12 @@
+use {
+    super::*,
+    serde::{Deserialize, Serialize},
+};
+
+/// A message that a peer receives on the connection.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Message {
+    Request(Request),
+    Response(Response),
+}
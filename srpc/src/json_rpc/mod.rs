@@ -3,17 +3,21 @@
 //! Note that for now, only JSON-RPC version 2.0 is supported.
 //!
 
+pub mod cancel;
 mod errors;
+mod message;
 mod request;
 mod response;
+pub mod subscription;
 
 pub use errors::*;
+pub use message::*;
 pub use request::*;
 pub use response::*;
 
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub enum Version {
     #[serde(rename = "2.0")]
     V2,
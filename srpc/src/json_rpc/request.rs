@@ -1,17 +1,21 @@
 use {
     super::*,
     serde::{Deserialize, Serialize},
-    serde_json::Value,
+    serde_json::{Map, Value},
     std::convert::TryFrom,
 };
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct Request {
     pub jsonrpc: Version,
     pub method: String,
     pub params: Value,
     pub id: Option<Id>,
+    /// Extension member for data that is not a part of the params, like auth tokens or trace
+    /// ids. It is not a part of JSON-RPC, so it is left out if there is none.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Map<String, Value>>,
 }
 
 impl TryFrom<&[u8]> for Request {
@@ -33,6 +37,7 @@ impl Request {
             method,
             params,
             id,
+            meta: None,
         }
     }
 }
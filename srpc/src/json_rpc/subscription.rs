@@ -0,0 +1,22 @@
+//! Notifications of the subscriptions. They are not a part of JSON-RPC, so their method names
+//! start with `$/`.
+
+use {
+    serde::{Deserialize, Serialize},
+    serde_json::Value,
+};
+
+/// Method of the notifications which carry the items of a subscription.
+pub const SUBSCRIPTION: &str = "$/subscription";
+/// Method of the notification which is sent when the server ends a subscription.
+pub const SUBSCRIPTION_END: &str = "$/subscriptionEnd";
+/// Method of the notification which the client sends to end a subscription.
+pub const UNSUBSCRIBE: &str = "$/unsubscribe";
+
+/// Params of the subscription notifications. Only the items have a result.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SubscriptionParams {
+    pub subscription: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+}
@@ -1,8 +1,13 @@
 pub mod client;
 pub mod json_rpc;
+#[cfg(feature = "openrpc")]
+pub mod openrpc;
+pub mod peer;
 pub mod server;
 pub mod transport;
 
 pub type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
 
+pub type BoxFuture<'a, T> = std::pin::Pin<Box<dyn std::future::Future<Output = T> + Send + 'a>>;
+
 pub use srpc_macros::*;
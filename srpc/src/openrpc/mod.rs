@@ -0,0 +1,238 @@
+//! OpenRPC documents of services.
+//!
+//! `#[srpc::service(openrpc)]` describes the service in an associated constant named
+//! `OPENRPC`. It contains the method names, parameter names, doc comments and the JSON Schemas
+//! of the parameter and result types, so every parameter and result type has to implement
+//! [JsonSchema](schemars::JsonSchema). The service also answers the built-in `rpc.discover`
+//! method with the [Document] of the service.
+//!
+//! Requires the `openrpc` feature.
+//!
+//! # Example
+//! ```no_run
+//! struct StrService;
+//!
+//! #[srpc::service(openrpc)]
+//! impl StrService {
+//!     /// Checks if 'elem' is a part of 'data'.
+//!     async fn contains(data: String, elem: String) -> bool {
+//!         data.contains(&elem)
+//!     }
+//! }
+//!
+//! let document = StrService::OPENRPC.document();
+//! println!("{}", serde_json::to_string_pretty(&document).unwrap());
+//! ```
+pub mod typescript;
+#[cfg(feature = "validation")]
+pub mod validation;
+
+pub use schemars;
+
+use {
+    schemars::{
+        gen::{SchemaGenerator, SchemaSettings},
+        schema::Schema,
+        Map,
+    },
+    serde::{Deserialize, Serialize},
+};
+
+/// Version of the OpenRPC specification that the documents follow.
+pub const OPENRPC_VERSION: &str = "1.2.6";
+
+/// Generates the schema of a type, e.g. `SchemaGenerator::subschema_for::<T>`. Schemas of the
+/// structs and enums go to the components of the document, so they are referenced instead of
+/// repeated.
+pub type SchemaFn = fn(&mut SchemaGenerator) -> Schema;
+
+/// Description of a service, generated by `#[srpc::service(openrpc)]`.
+pub struct ServiceDescription {
+    pub title: &'static str,
+    pub version: &'static str,
+    pub description: Option<&'static str>,
+    pub methods: &'static [MethodDescription],
+}
+
+/// Description of an RPC method.
+pub struct MethodDescription {
+    /// Name of the method on the wire
+    pub name: &'static str,
+    /// Other names that the service accepts for the method, given with `#[rpc(alias = "..")]`
+    pub aliases: &'static [&'static str],
+    pub description: Option<&'static str>,
+    /// Parameters that are sent by the client. Reserved parameters are not included.
+    pub params: &'static [ParamDescription],
+    /// Schema of the result. Methods returning `Result<T, E>` are described by `T`.
+    pub result: SchemaFn,
+}
+
+/// Description of an RPC parameter.
+pub struct ParamDescription {
+    pub name: &'static str,
+    /// Parameters of type `Option<T>` can be left out.
+    pub required: bool,
+    pub schema: SchemaFn,
+}
+
+impl ServiceDescription {
+    /// Generates the OpenRPC document of the service.
+    pub fn document(&self) -> Document {
+        let mut gen = SchemaSettings::draft07()
+            .with(|settings| settings.definitions_path = String::from("#/components/schemas/"))
+            .into_generator();
+
+        let methods = self
+            .methods
+            .iter()
+            .map(|method| Method {
+                name: String::from(method.name),
+                description: method.description.map(String::from),
+                params: method
+                    .params
+                    .iter()
+                    .map(|param| ContentDescriptor {
+                        name: String::from(param.name),
+                        description: None,
+                        required: param.required,
+                        schema: (param.schema)(&mut gen),
+                    })
+                    .collect(),
+                result: Some(ContentDescriptor {
+                    name: String::from("result"),
+                    description: None,
+                    required: true,
+                    schema: (method.result)(&mut gen),
+                }),
+            })
+            .collect();
+
+        Document {
+            openrpc: String::from(OPENRPC_VERSION),
+            info: Info {
+                title: String::from(self.title),
+                version: String::from(self.version),
+                description: self.description.map(String::from),
+            },
+            methods,
+            components: Components {
+                schemas: gen.take_definitions(),
+            },
+        }
+    }
+}
+
+/// An OpenRPC document. Only the parts that srpc generates are supported.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Document {
+    pub openrpc: String,
+    pub info: Info,
+    pub methods: Vec<Method>,
+    #[serde(default)]
+    pub components: Components,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Info {
+    pub title: String,
+    pub version: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Method {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub params: Vec<ContentDescriptor>,
+    /// The generated documents always have a result. Methods returning `()` are described by
+    /// `null`, and subscriptions by the id of the subscription. Documents which leave it out
+    /// describe notifications.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<ContentDescriptor>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContentDescriptor {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub required: bool,
+    pub schema: Schema,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Components {
+    #[serde(default)]
+    pub schemas: Map<String, Schema>,
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, schemars::JsonSchema, serde_json::json};
+
+    #[derive(JsonSchema)]
+    #[allow(unused)]
+    struct Point {
+        x: i32,
+        y: i32,
+    }
+
+    const SERVICE: ServiceDescription = ServiceDescription {
+        title: "Geometry",
+        version: "0.1.0",
+        description: Some("Geometry service"),
+        methods: &[MethodDescription {
+            name: "distance",
+            aliases: &[],
+            description: None,
+            params: &[
+                ParamDescription {
+                    name: "from",
+                    required: true,
+                    schema: SchemaGenerator::subschema_for::<Point>,
+                },
+                ParamDescription {
+                    name: "to",
+                    required: false,
+                    schema: SchemaGenerator::subschema_for::<Option<Point>>,
+                },
+            ],
+            result: SchemaGenerator::subschema_for::<f64>,
+        }],
+    };
+
+    #[test]
+    fn document() {
+        let document = serde_json::to_value(SERVICE.document()).unwrap();
+
+        assert_eq!(document["openrpc"], json!(OPENRPC_VERSION));
+        assert_eq!(
+            document["info"],
+            json!({ "title": "Geometry", "version": "0.1.0", "description": "Geometry service" })
+        );
+
+        let method = &document["methods"][0];
+        assert_eq!(method["name"], json!("distance"));
+        assert_eq!(method["params"][0]["name"], json!("from"));
+        assert_eq!(method["params"][0]["required"], json!(true));
+        assert_eq!(
+            method["params"][0]["schema"],
+            json!({ "$ref": "#/components/schemas/Point" })
+        );
+        assert_eq!(method["params"][1]["required"], json!(false));
+        assert_eq!(method["result"]["schema"]["type"], json!("number"));
+
+        let point = &document["components"]["schemas"]["Point"];
+        assert_eq!(point["required"], json!(["x", "y"]));
+    }
+
+    #[test]
+    fn document_round_trip() {
+        let document = SERVICE.document();
+        let value = serde_json::to_value(&document).unwrap();
+        assert_eq!(serde_json::from_value::<Document>(value).unwrap(), document);
+    }
+}
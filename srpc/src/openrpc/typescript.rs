@@ -0,0 +1,556 @@
+//! TypeScript clients of OpenRPC documents.
+//!
+//! The generated file has an interface or a type alias for each schema in the components of
+//! the document, and a client class with a typed function for each method. Requests are sent
+//! through a `Transport`, and the file comes with an `HttpTransport` which posts the requests
+//! with `fetch` and a `WebSocketTransport` which keeps a WebSocket open.
+//!
+//! Neither of them can talk to an srpc server directly: the server only speaks JSON-RPC over
+//! TCP, with each message prefixed by its length, which browsers can not do. The service has to
+//! be reachable through an HTTP or WebSocket endpoint which forwards the requests, or the
+//! frontend brings its own `Transport`.
+//!
+//! Parameters named after reserved words of TypeScript, e.g. `default` or `new`, get a `_`
+//! suffix in the signatures. They are still sent with their names in the document.
+//!
+//! Generating the file is a part of the build, e.g. in a test or a small binary:
+//! ```no_run
+//! let document = StrService::OPENRPC.document();
+//! std::fs::write("web/src/str_service.ts", srpc::openrpc::typescript::client(&document))?;
+//! ```
+//!
+//! On the frontend:
+//! ```text
+//! const service = new StrServiceClient(new HttpTransport("https://example.com/rpc"));
+//! const contains = await service.contains("cool lib", "lib");
+//! ```
+use {
+    super::{Document, Method},
+    schemars::schema::{InstanceType, Schema, SchemaObject, SingleOrVec},
+    serde_json::Value,
+    std::fmt::Write,
+};
+
+/// Transports that the generated clients use. They need an HTTP or WebSocket endpoint in front of
+/// the srpc server, which only accepts length-prefixed messages over TCP.
+const TRANSPORTS: &str = r#"export interface Transport {
+  call(method: string, params: unknown): Promise<unknown>;
+  notify(method: string, params: unknown): Promise<void>;
+}
+
+export class RpcError extends Error {
+  constructor(public code: number, message: string, public data?: unknown) {
+    super(message);
+  }
+}
+
+function result(response: any): unknown {
+  if (response.error) {
+    throw new RpcError(response.error.code, response.error.message, response.error.data);
+  }
+  return response.result;
+}
+
+/**
+ * Posts each request to the URL. srpc servers don't speak HTTP, so the URL has to be an
+ * endpoint which forwards the requests to the server.
+ */
+export class HttpTransport implements Transport {
+  private id = 0;
+
+  constructor(private url: string, private init: RequestInit = {}) {}
+
+  async call(method: string, params: unknown): Promise<unknown> {
+    return result(await this.post({ jsonrpc: "2.0", method, params, id: ++this.id }));
+  }
+
+  async notify(method: string, params: unknown): Promise<void> {
+    await this.post({ jsonrpc: "2.0", method, params });
+  }
+
+  private async post(request: object): Promise<any> {
+    const response = await fetch(this.url, {
+      ...this.init,
+      method: "POST",
+      headers: { "Content-Type": "application/json", ...this.init.headers },
+      body: JSON.stringify(request),
+    });
+    const text = await response.text();
+    return text ? JSON.parse(text) : undefined;
+  }
+}
+
+/**
+ * Sends every request over the same WebSocket. srpc servers don't speak WebSocket, so the
+ * socket has to be connected to an endpoint which forwards the requests to the server.
+ */
+export class WebSocketTransport implements Transport {
+  private id = 0;
+  private pending = new Map<number, (response: any) => void>();
+  private open: Promise<void>;
+
+  constructor(private socket: WebSocket) {
+    this.open = new Promise((resolve, reject) => {
+      if (socket.readyState === WebSocket.OPEN) {
+        resolve();
+      } else {
+        socket.addEventListener("open", () => resolve(), { once: true });
+        socket.addEventListener("error", () => reject(new Error("WebSocket error")), { once: true });
+      }
+    });
+    socket.addEventListener("message", (event) => {
+      const response = JSON.parse(event.data);
+      const resolve = this.pending.get(response.id);
+      if (resolve) {
+        this.pending.delete(response.id);
+        resolve(response);
+      }
+    });
+  }
+
+  async call(method: string, params: unknown): Promise<unknown> {
+    await this.open;
+    const id = ++this.id;
+    const response = new Promise<any>((resolve) => this.pending.set(id, resolve));
+    this.socket.send(JSON.stringify({ jsonrpc: "2.0", method, params, id }));
+    return result(await response);
+  }
+
+  async notify(method: string, params: unknown): Promise<void> {
+    await this.open;
+    this.socket.send(JSON.stringify({ jsonrpc: "2.0", method, params }));
+  }
+}
+"#;
+
+/// Generates the TypeScript client of the document.
+pub fn client(document: &Document) -> String {
+    let mut ts = String::new();
+    let _ = writeln!(
+        ts,
+        "// Generated by srpc from the OpenRPC document of {} {}. Do not edit.\n",
+        document.info.title, document.info.version
+    );
+
+    for (name, schema) in &document.components.schemas {
+        ts.push_str(&doc_comment(description(schema), ""));
+        match object_type(schema, "") {
+            Some(object) => {
+                let _ = writeln!(ts, "export interface {} {}\n", type_name(name), object);
+            }
+            None => {
+                let _ = writeln!(
+                    ts,
+                    "export type {} = {};\n",
+                    type_name(name),
+                    ts_type(schema)
+                );
+            }
+        }
+    }
+
+    ts.push_str(TRANSPORTS);
+    ts.push('\n');
+
+    ts.push_str(&doc_comment(document.info.description.as_deref(), ""));
+    let _ = writeln!(
+        ts,
+        "export class {}Client {{\n  constructor(private transport: Transport) {{}}",
+        type_name(&document.info.title)
+    );
+    for method in &document.methods {
+        ts.push('\n');
+        ts.push_str(&client_method(method));
+    }
+    ts.push_str("}\n");
+    ts
+}
+
+fn client_method(method: &Method) -> String {
+    // Optional parameters can only be marked with '?' if the rest are optional too.
+    let optional_from = method
+        .params
+        .iter()
+        .rposition(|param| param.required)
+        .map_or(0, |i| i + 1);
+    let names = param_names(method.params.iter().map(|param| param.name.as_str()));
+    let params: Vec<_> = method
+        .params
+        .iter()
+        .zip(&names)
+        .enumerate()
+        .map(|(i, (param, name))| {
+            let ts_type = ts_type(&param.schema);
+            match (param.required, i >= optional_from) {
+                (true, _) => format!("{}: {}", name, ts_type),
+                (false, true) => format!("{}?: {}", name, ts_type),
+                (false, false) => format!("{}: {} | undefined", name, ts_type),
+            }
+        })
+        .collect();
+    // Parameters are sent by the names in the document.
+    let args: Vec<_> = method
+        .params
+        .iter()
+        .zip(&names)
+        .map(|(param, name)| {
+            if param.name == *name {
+                param.name.clone()
+            } else {
+                format!("{}: {}", property_name(&param.name), name)
+            }
+        })
+        .collect();
+
+    let mut ts = doc_comment(method.description.as_deref(), "  ");
+    let name = camel_case(&method.name);
+    let _ = match &method.result {
+        Some(result) => {
+            let result = ts_type(&result.schema);
+            writeln!(
+                ts,
+                "  async {}({}): Promise<{}> {{\n    return (await this.transport.call({:?}, {{ {} }})) as {};\n  }}",
+                name,
+                params.join(", "),
+                result,
+                method.name,
+                args.join(", "),
+                result
+            )
+        }
+        // Methods without a result are notifications.
+        None => writeln!(
+            ts,
+            "  async {}({}): Promise<void> {{\n    await this.transport.notify({:?}, {{ {} }});\n  }}",
+            name,
+            params.join(", "),
+            method.name,
+            args.join(", ")
+        ),
+    };
+    ts
+}
+
+/// Returns the TypeScript type of the schema.
+fn ts_type(schema: &Schema) -> String {
+    let schema = match schema {
+        Schema::Bool(_) => return String::from("unknown"),
+        Schema::Object(schema) => schema,
+    };
+
+    if let Some(reference) = &schema.reference {
+        return type_name(reference.rsplit('/').next().unwrap_or(reference));
+    }
+    if let Some(values) = &schema.enum_values {
+        return union(values.iter().map(Value::to_string));
+    }
+    if let Some(value) = &schema.const_value {
+        return value.to_string();
+    }
+    if let Some(subschemas) = &schema.subschemas {
+        if let Some(schemas) = subschemas.any_of.as_ref().or(subschemas.one_of.as_ref()) {
+            return union(schemas.iter().map(ts_type));
+        }
+        if let Some(schemas) = &subschemas.all_of {
+            return schemas
+                .iter()
+                .map(|schema| wrap(ts_type(schema)))
+                .collect::<Vec<_>>()
+                .join(" & ");
+        }
+    }
+
+    match &schema.instance_type {
+        Some(SingleOrVec::Single(instance_type)) => instance_ts_type(instance_type, schema),
+        Some(SingleOrVec::Vec(instance_types)) => union(
+            instance_types
+                .iter()
+                .map(|instance_type| instance_ts_type(instance_type, schema)),
+        ),
+        None => String::from("unknown"),
+    }
+}
+
+fn instance_ts_type(instance_type: &InstanceType, schema: &SchemaObject) -> String {
+    match instance_type {
+        InstanceType::Null => String::from("null"),
+        InstanceType::Boolean => String::from("boolean"),
+        InstanceType::Integer | InstanceType::Number => String::from("number"),
+        InstanceType::String => String::from("string"),
+        InstanceType::Array => match schema.array.as_ref().and_then(|array| array.items.as_ref()) {
+            Some(SingleOrVec::Single(items)) => format!("{}[]", wrap(ts_type(items))),
+            Some(SingleOrVec::Vec(items)) => format!(
+                "[{}]",
+                items.iter().map(ts_type).collect::<Vec<_>>().join(", ")
+            ),
+            None => String::from("unknown[]"),
+        },
+        InstanceType::Object => object_type(&Schema::Object(schema.clone()), "")
+            .or_else(|| {
+                let additional = schema.object.as_ref()?.additional_properties.as_ref()?;
+                Some(format!("Record<string, {}>", ts_type(additional)))
+            })
+            .unwrap_or_else(|| String::from("Record<string, unknown>")),
+    }
+}
+
+/// Returns the object literal type of a schema with properties, e.g. '{ x: number }'.
+fn object_type(schema: &Schema, indent: &str) -> Option<String> {
+    let object = match schema {
+        Schema::Object(SchemaObject {
+            object: Some(object),
+            ..
+        }) if !object.properties.is_empty() => object,
+        _ => return None,
+    };
+
+    let mut ts = String::from("{\n");
+    for (name, property) in &object.properties {
+        ts.push_str(&doc_comment(
+            description(property),
+            &format!("{}  ", indent),
+        ));
+        let optional = if object.required.contains(name) {
+            ""
+        } else {
+            "?"
+        };
+        let _ = writeln!(
+            ts,
+            "{}  {}{}: {};",
+            indent,
+            property_name(name),
+            optional,
+            ts_type(property)
+        );
+    }
+    ts.push_str(indent);
+    ts.push('}');
+    Some(ts)
+}
+
+fn description(schema: &Schema) -> Option<&str> {
+    match schema {
+        Schema::Object(SchemaObject {
+            metadata: Some(metadata),
+            ..
+        }) => metadata.description.as_deref(),
+        _ => None,
+    }
+}
+
+fn doc_comment(description: Option<&str>, indent: &str) -> String {
+    match description {
+        Some(description) => {
+            let mut ts = format!("{}/**\n", indent);
+            for line in description.lines() {
+                let _ = writeln!(ts, "{} * {}", indent, line);
+            }
+            let _ = writeln!(ts, "{} */", indent);
+            ts
+        }
+        None => String::new(),
+    }
+}
+
+fn union(types: impl Iterator<Item = String>) -> String {
+    types.map(wrap).collect::<Vec<_>>().join(" | ")
+}
+
+/// Puts the unions and intersections in parentheses, so they can be a part of another type.
+fn wrap(ts_type: String) -> String {
+    if ts_type.contains(" | ") || ts_type.contains(" & ") {
+        format!("({})", ts_type)
+    } else {
+        ts_type
+    }
+}
+
+/// Words which can not be the names of parameters in TypeScript.
+const RESERVED_WORDS: &str = "arguments await break case catch class const continue debugger \
+    default delete do else enum eval export extends false finally for function if implements \
+    import in instanceof interface let new null package private protected public return static \
+    super switch this throw true try typeof var void while with yield";
+
+/// Returns unique TypeScript names of the parameters. Reserved words get a '_' suffix, and names
+/// which are not identifiers are camel cased.
+fn param_names<'a>(names: impl Iterator<Item = &'a str>) -> Vec<String> {
+    let mut unique: Vec<String> = Vec::new();
+    for name in names {
+        let mut ts_name = if is_ident(name) {
+            String::from(name)
+        } else {
+            camel_case(name)
+        };
+        if ts_name.is_empty() || ts_name.starts_with(|c: char| c.is_ascii_digit()) {
+            ts_name.insert(0, '_');
+        }
+        while is_reserved(&ts_name) || unique.contains(&ts_name) {
+            ts_name.push('_');
+        }
+        unique.push(ts_name);
+    }
+    unique
+}
+
+fn is_reserved(name: &str) -> bool {
+    RESERVED_WORDS.split_whitespace().any(|word| word == name)
+}
+
+fn is_ident(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
+
+/// Property names which are not identifiers are quoted.
+fn property_name(name: &str) -> String {
+    if is_ident(name) {
+        String::from(name)
+    } else {
+        format!("{:?}", name)
+    }
+}
+
+/// 'Point_for_int32' => 'PointForInt32'
+fn type_name(name: &str) -> String {
+    let name = camel_case(name);
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+        None => name,
+    }
+}
+
+/// 'geo_distance_between' => 'geoDistanceBetween'
+fn camel_case(name: &str) -> String {
+    let mut camel = String::new();
+    let mut upper = false;
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() {
+            if upper && !camel.is_empty() {
+                camel.push(c.to_ascii_uppercase());
+            } else {
+                camel.push(c);
+            }
+            upper = false;
+        } else {
+            upper = true;
+        }
+    }
+    camel
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, serde_json::json};
+
+    fn document() -> Document {
+        serde_json::from_value(json!({
+            "openrpc": "1.2.6",
+            "info": { "title": "Geometry", "version": "0.1.0" },
+            "methods": [
+                {
+                    "name": "geo_distance",
+                    "description": "Returns the distance between two points.",
+                    "params": [
+                        { "name": "from", "required": true, "schema": { "$ref": "#/components/schemas/Point" } },
+                        {
+                            "name": "to",
+                            "schema": { "anyOf": [{ "$ref": "#/components/schemas/Point" }, { "type": "null" }] }
+                        },
+                    ],
+                    "result": { "name": "result", "schema": { "type": "number", "format": "double" } },
+                },
+                {
+                    "name": "geo_reset",
+                    "params": [
+                        { "name": "units", "schema": { "type": "array", "items": { "type": ["string", "null"] } } },
+                        { "name": "force", "required": true, "schema": { "type": "boolean" } },
+                    ],
+                },
+            ],
+            "components": {
+                "schemas": {
+                    "Point": {
+                        "type": "object",
+                        "required": ["x", "y"],
+                        "properties": {
+                            "x": { "type": "integer", "format": "int32" },
+                            "y": { "type": "integer", "format": "int32" },
+                            "unit-of-length": {
+                                "description": "Meters by default",
+                                "type": "string",
+                                "enum": ["m", "km"],
+                            },
+                        },
+                    },
+                    "Tags": { "type": "object", "additionalProperties": { "type": "string" } },
+                },
+            },
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn types() {
+        let ts = client(&document());
+
+        assert!(ts.contains(
+            "export interface Point {\n  \
+               /**\n   * Meters by default\n   */\n  \
+               \"unit-of-length\"?: \"m\" | \"km\";\n  \
+               x: number;\n  \
+               y: number;\n\
+             }\n"
+        ));
+        assert!(ts.contains("export type Tags = Record<string, string>;\n"));
+    }
+
+    #[test]
+    fn methods() {
+        let ts = client(&document());
+
+        assert!(ts.contains("export class GeometryClient {\n"));
+        assert!(ts.contains(
+            "  /**\n   * Returns the distance between two points.\n   */\n  \
+             async geoDistance(from: Point, to?: Point | null): Promise<number> {\n    \
+               return (await this.transport.call(\"geo_distance\", { from, to })) as number;\n  \
+             }\n"
+        ));
+        // Notification with an optional parameter before a required one
+        assert!(ts.contains(
+            "  async geoReset(units: (string | null)[] | undefined, force: boolean): Promise<void> {\n    \
+               await this.transport.notify(\"geo_reset\", { units, force });\n  \
+             }\n"
+        ));
+    }
+    #[test]
+    fn reserved_param_names() {
+        let document = serde_json::from_value(json!({
+            "openrpc": "1.2.6",
+            "info": { "title": "Shapes", "version": "0.1.0" },
+            "methods": [
+                {
+                    "name": "create",
+                    "params": [
+                        { "name": "class", "required": true, "schema": { "type": "string" } },
+                        { "name": "new", "required": true, "schema": { "type": "boolean" } },
+                        { "name": "max-size", "required": true, "schema": { "type": "number" } },
+                        { "name": "function", "schema": { "type": "string" } },
+                        { "name": "default", "schema": { "type": "number" } },
+                    ],
+                },
+            ],
+        }))
+        .unwrap();
+        let ts = client(&document);
+
+        assert!(ts.contains(
+            "  async create(class_: string, new_: boolean, maxSize: number, function_?: string, default_?: number): Promise<void> {\n    \
+               await this.transport.notify(\"create\", { class: class_, new: new_, \"max-size\": maxSize, function: function_, default: default_ });\n  \
+             }\n"
+        ));
+    }
+}
@@ -0,0 +1,306 @@
+//! Validation of the params against the schemas of the methods.
+//!
+//! Deserialization errors only tell the first problem of the params, in the words of serde. A
+//! [Validator] checks the params against the JSON Schemas in the [ServiceDescription] before
+//! the method is called, so ranges, string patterns and required fields are checked too. Every
+//! violation is reported in the data of the `InvalidParams` error, with the JSON pointer of the
+//! value that violates the schema:
+//! ```json
+//! {
+//!   "code": -32602,
+//!   "message": "Invalid params",
+//!   "data": {
+//!     "violations": [
+//!       { "path": "/from/x", "message": "\"3\" is not of type \"integer\"" },
+//!       { "path": "", "message": "\"to\" is a required property" }
+//!     ]
+//!   }
+//! }
+//! ```
+//!
+//! Positional params are checked too. The elements of an array are matched with the params in
+//! the order they are declared, and the paths of their violations start with the index of the
+//! param, e.g. `/0/x`. Elements beyond the declared params are violations.
+//!
+//! Constraints are added to the schemas with the attributes of schemars, e.g.
+//! `#[schemars(range(min = 1))]` or `#[schemars(regex(pattern = r"^\d+$"))]`.
+//!
+//! Requires the `validation` feature.
+//!
+//! # Example
+//! ```no_run
+//! let mut server = Server::new(StrService, StrService::caller);
+//! server.set_validator(Validator::new(&StrService::OPENRPC)?);
+//! ```
+use {
+    super::{schemars::gen::SchemaSettings, ParamDescription, ServiceDescription},
+    crate::json_rpc,
+    jsonschema::{Draft, JSONSchema},
+    serde::{Deserialize, Serialize},
+    serde_json::{json, Map, Value},
+    std::{collections::HashMap, sync::Arc},
+};
+
+/// Validates the params of the methods of a service.
+pub struct Validator {
+    // Keyed by the name and the aliases of the methods
+    methods: HashMap<&'static str, Arc<Method>>,
+}
+
+struct Method {
+    schema: JSONSchema,
+    params: &'static [ParamDescription],
+}
+
+/// A part of the params that violates the schema of the method.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Violation {
+    /// JSON pointer of the value in the params
+    pub path: String,
+    pub message: String,
+}
+
+impl Validator {
+    /// Compiles the schemas of the params of each method. Calls through the aliases of a
+    /// method are checked against the same schema.
+    pub fn new(description: &ServiceDescription) -> crate::Result<Self> {
+        let mut methods = HashMap::new();
+        for method in description.methods {
+            let mut gen = SchemaSettings::draft07()
+                .with(|settings| settings.definitions_path = String::from("#/definitions/"))
+                .into_generator();
+            let mut properties = Map::new();
+            let mut required = Vec::new();
+            for param in method.params {
+                properties.insert(
+                    String::from(param.name),
+                    serde_json::to_value((param.schema)(&mut gen))?,
+                );
+                if param.required {
+                    required.push(param.name);
+                }
+            }
+
+            let schema = json!({
+                "type": "object",
+                "properties": properties,
+                "required": required,
+                "definitions": gen.take_definitions(),
+            });
+            let schema = JSONSchema::options()
+                .with_draft(Draft::Draft7)
+                .compile(&schema)
+                .map_err(|e| format!("Invalid schema of '{}': {}", method.name, e))?;
+            let compiled = Arc::new(Method {
+                schema,
+                params: method.params,
+            });
+            for name in std::iter::once(&method.name).chain(method.aliases) {
+                methods.insert(*name, compiled.clone());
+            }
+        }
+
+        Ok(Self { methods })
+    }
+
+    /// Returns every violation of the params. Methods that are not described are not checked.
+    pub fn violations(&self, method: &str, params: &Value) -> Vec<Violation> {
+        let method = match self.methods.get(method) {
+            Some(method) => method,
+            None => return Vec::new(),
+        };
+        match params {
+            // Clients send null if there are no params.
+            Value::Null => method.violations(&Value::Object(Map::new())),
+            Value::Array(elements) => method.positional_violations(elements),
+            params => method.violations(params),
+        }
+    }
+
+    /// Returns an `InvalidParams` error listing the violations, if there are any.
+    pub fn validate(&self, method: &str, params: &Value) -> Result<(), json_rpc::Error> {
+        let violations = self.violations(method, params);
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(json_rpc::Error::new(
+                json_rpc::ErrorKind::InvalidParams,
+                Some(json!({ "violations": violations })),
+            ))
+        }
+    }
+}
+
+impl Method {
+    fn violations(&self, params: &Value) -> Vec<Violation> {
+        match self.schema.validate(params) {
+            Ok(()) => Vec::new(),
+            Err(errors) => errors
+                .map(|error| Violation {
+                    path: error.instance_path.to_string(),
+                    message: error.to_string(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Checks the elements as the params they are matched with, and reports the paths with the
+    /// indices of the params.
+    fn positional_violations(&self, elements: &[Value]) -> Vec<Violation> {
+        let named: Map<String, Value> = self
+            .params
+            .iter()
+            .zip(elements)
+            .map(|(param, element)| (String::from(param.name), element.clone()))
+            .collect();
+
+        let mut violations: Vec<Violation> = self
+            .violations(&Value::Object(named))
+            .into_iter()
+            .map(|mut violation| {
+                violation.path = self.positional_path(&violation.path);
+                violation
+            })
+            .collect();
+        for index in self.params.len()..elements.len() {
+            violations.push(Violation {
+                path: format!("/{}", index),
+                message: format!("expected at most {} params", self.params.len()),
+            });
+        }
+        violations
+    }
+
+    /// Replaces the name of the param at the start of the path with its index.
+    fn positional_path(&self, path: &str) -> String {
+        for (index, param) in self.params.iter().enumerate() {
+            if let Some(rest) = path
+                .strip_prefix('/')
+                .and_then(|p| p.strip_prefix(param.name))
+            {
+                if rest.is_empty() || rest.starts_with('/') {
+                    return format!("/{}{}", index, rest);
+                }
+            }
+        }
+        String::from(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::openrpc::{
+            schemars::{gen::SchemaGenerator, JsonSchema},
+            MethodDescription, ParamDescription,
+        },
+    };
+
+    #[derive(JsonSchema)]
+    #[allow(unused)]
+    struct Point {
+        #[schemars(range(min = 0))]
+        x: i32,
+        y: i32,
+        #[schemars(regex(pattern = r"^[a-z]+$"))]
+        label: Option<String>,
+    }
+
+    const SERVICE: ServiceDescription = ServiceDescription {
+        title: "Geometry",
+        version: "0.1.0",
+        description: None,
+        methods: &[
+            MethodDescription {
+                name: "distance",
+                aliases: &["length"],
+                description: None,
+                params: &[
+                    ParamDescription {
+                        name: "from",
+                        required: true,
+                        schema: SchemaGenerator::subschema_for::<Point>,
+                    },
+                    ParamDescription {
+                        name: "to",
+                        required: true,
+                        schema: SchemaGenerator::subschema_for::<Point>,
+                    },
+                ],
+                result: SchemaGenerator::subschema_for::<f64>,
+            },
+            MethodDescription {
+                name: "reset",
+                aliases: &[],
+                description: None,
+                params: &[],
+                result: SchemaGenerator::subschema_for::<()>,
+            },
+        ],
+    };
+
+    #[test]
+    fn valid_params() {
+        let validator = Validator::new(&SERVICE).unwrap();
+        let params = json!({ "from": { "x": 1, "y": 2, "label": "a" }, "to": { "x": 0, "y": 0 } });
+        assert!(validator.validate("distance", &params).is_ok());
+        assert!(validator.validate("reset", &Value::Null).is_ok());
+        assert!(validator.validate("unknown", &json!(3)).is_ok());
+    }
+
+    #[test]
+    fn every_violation_is_reported() {
+        let validator = Validator::new(&SERVICE).unwrap();
+        let params = json!({ "from": { "x": -1, "y": "2", "label": "A" } });
+
+        let mut paths: Vec<_> = validator
+            .violations("distance", &params)
+            .into_iter()
+            .map(|violation| violation.path)
+            .collect();
+        paths.sort();
+        assert_eq!(paths, vec!["", "/from/label", "/from/x", "/from/y"]);
+
+        let error = validator.validate("distance", &params).unwrap_err();
+        assert_eq!(error.kind, json_rpc::ErrorKind::InvalidParams);
+        assert_eq!(
+            error.data.unwrap()["violations"].as_array().unwrap().len(),
+            4
+        );
+    }
+
+    #[test]
+    fn aliases_are_validated() {
+        let validator = Validator::new(&SERVICE).unwrap();
+        let params = json!({ "from": { "x": -1, "y": 2 }, "to": { "x": 0, "y": 0 } });
+
+        let paths: Vec<_> = validator
+            .violations("length", &params)
+            .into_iter()
+            .map(|violation| violation.path)
+            .collect();
+        assert_eq!(paths, vec!["/from/x"]);
+    }
+
+    #[test]
+    fn positional_params_are_validated() {
+        let validator = Validator::new(&SERVICE).unwrap();
+        let params = json!([{ "x": 1, "y": 2 }, { "x": 0, "y": 0 }]);
+        assert!(validator.validate("distance", &params).is_ok());
+
+        let params = json!([{ "x": 1, "y": 2 }, { "x": -1, "y": 0 }, 3]);
+        let mut paths: Vec<_> = validator
+            .violations("distance", &params)
+            .into_iter()
+            .map(|violation| violation.path)
+            .collect();
+        paths.sort();
+        assert_eq!(paths, vec!["/1/x", "/2"]);
+
+        // Missing params are reported as missing properties.
+        let violations = validator.violations("distance", &json!([{ "x": 1, "y": 2 }]));
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].path, "");
+    }
+}
@@ -0,0 +1,92 @@
+//! Peers that serve and call RPC methods on the same connection.
+//!
+//! A [Client] only calls the server and a [Server] only answers its callers. When the server
+//! needs to call back, e.g. to ask the caller for confirmation, both sides of the connection
+//! serve a service and both sides make calls.
+//!
+//! The side that connects uses [Peer::connect] with the server of its own service. The server
+//! side gets the peer of a connection from the context of a call with
+//! [Context::peer](crate::server::Context::peer). Calls are made with the clients which are
+//! generated by `#[srpc::client]`, over the [Client] of the peer.
+//!
+//! ```no_run
+//! use {
+//!     srpc::{peer::Peer, server::{Context, Server}},
+//!     std::sync::Arc,
+//! };
+//!
+//! #[srpc::client]
+//! trait Editor {
+//!     async fn confirm(question: String) -> bool;
+//! }
+//!
+//! #[srpc::client]
+//! trait Files {
+//!     async fn delete(path: String) -> bool;
+//! }
+//!
+//! struct FileService;
+//!
+//! #[srpc::service]
+//! impl FileService {
+//!     async fn delete(context: Arc<Context>, path: String) -> bool {
+//!         let editor = EditorClient::new(context.peer().unwrap().client());
+//!         editor.confirm(format!("Delete {}?", path)).await.unwrap_or(false)
+//!     }
+//! }
+//!
+//! struct EditorService;
+//!
+//! #[srpc::service]
+//! impl EditorService {
+//!     fn confirm(question: String) -> bool {
+//!         true
+//!     }
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     tokio::spawn(Server::new(FileService, FileService::caller).serve("127.0.0.1:8080"));
+//!
+//!     let server = Server::new(EditorService, EditorService::caller);
+//!     let peer = Peer::connect(([127, 0, 0, 1], 8080).into(), server).await.unwrap();
+//!     let files = FilesClient::new(peer.client());
+//!     files.delete(String::from("cool.txt")).await.unwrap();
+//! }
+//! ```
+//!
+//! Notifications from the peer are dispatched to the service, except the items of the
+//! subscriptions which are sent to their [Subscription](crate::client::Subscription).
+//!
+
+use {
+    crate::{client::Client, server::Server},
+    std::{net::SocketAddr, sync::Arc},
+};
+
+/// The other side of a connection, which serves RPC methods to this side.
+#[derive(Clone)]
+pub struct Peer {
+    client: Client,
+}
+
+impl Peer {
+    pub(crate) fn new(client: Client) -> Self {
+        Self { client }
+    }
+
+    /// Connects to the server at the address and serves the methods of `server` to it on the
+    /// same connection. The hooks of `server` run for the connection too.
+    pub async fn connect<T>(addr: SocketAddr, server: Server<T>) -> crate::Result<Self>
+    where
+        T: 'static + Send + Sync,
+    {
+        Arc::new(server).connect(addr).await
+    }
+
+    /// Returns a client which calls the methods of the peer. The client does not reconnect
+    /// when the connection closes.
+    pub fn client(&self) -> Client {
+        self.client.clone()
+    }
+}
@@ -0,0 +1,78 @@
+use {
+    crate::json_rpc::{self, cancel::CancelParams},
+    futures::future::{AbortHandle, AbortRegistration},
+    std::{
+        collections::HashMap,
+        sync::{Arc, Mutex},
+    },
+};
+
+#[derive(Default)]
+struct Tasks {
+    last_key: u64,
+    running: HashMap<u64, AbortHandle>,
+    // Keys of the tasks of the calls by their request ids, so the caller can cancel them
+    calls: HashMap<json_rpc::Id, u64>,
+}
+
+/// Tasks that handle the requests of a connection. They are aborted when the caller cancels
+/// the call or when the connection closes.
+#[derive(Clone, Default)]
+pub(super) struct Running(Arc<Mutex<Tasks>>);
+
+impl Running {
+    /// Registers the task of a request, which is aborted through the returned registration.
+    /// Returns the key to finish the task with.
+    pub fn start(&self, id: Option<json_rpc::Id>) -> (u64, AbortRegistration) {
+        let (handle, registration) = AbortHandle::new_pair();
+        let mut tasks = self.0.lock().unwrap();
+        tasks.last_key += 1;
+        let key = tasks.last_key;
+        tasks.running.insert(key, handle);
+        if let Some(id) = id {
+            tasks.calls.insert(id, key);
+        }
+        (key, registration)
+    }
+
+    pub fn finish(&self, key: u64, id: Option<&json_rpc::Id>) {
+        let mut tasks = self.0.lock().unwrap();
+        tasks.running.remove(&key);
+        if let Some(id) = id {
+            // The id might be reused by a newer call.
+            if tasks.calls.get(id) == Some(&key) {
+                tasks.calls.remove(id);
+            }
+        }
+    }
+
+    /// Aborts the call of the cancel notification. The call does not send a response.
+    pub fn cancel(&self, notification: json_rpc::Request) {
+        let params: CancelParams = match serde_json::from_value(notification.params) {
+            Ok(params) => params,
+            Err(e) => {
+                log::warn!("Invalid cancel notification: {}. Ignoring.", e);
+                return;
+            }
+        };
+        let mut tasks = self.0.lock().unwrap();
+        let handle = tasks
+            .calls
+            .remove(&params.id)
+            .and_then(|key| tasks.running.remove(&key));
+        match handle {
+            Some(handle) => handle.abort(),
+            // The call might have finished already.
+            None => log::debug!("Call {:?} is not running. Ignoring.", params.id),
+        }
+    }
+
+    /// Aborts every task, e.g. when the connection is closed.
+    pub fn cancel_all(&self) {
+        let mut tasks = self.0.lock().unwrap();
+        tasks.calls.clear();
+        for (_, handle) in tasks.running.drain() {
+            handle.abort();
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use {
+    super::Context,
+    crate::json_rpc,
+    futures::future::AbortHandle,
+    serde::Serialize,
+    std::{
+        collections::BTreeMap,
+        sync::{Arc, Mutex},
+    },
+    tokio::sync::oneshot,
+};
+
+struct Connection {
+    context: Arc<Context>,
+    // Stops reading the connection
+    abort: AbortHandle,
+    // Shuts the writer of the connection down
+    close: oneshot::Sender<()>,
+}
+
+/// Live connections of a server. Clones of it share the same connections, so it can be taken
+/// with [Server::connections](super::Server::connections) before the server is started.
+///
+/// ```no_run
+/// let connections = server.connections();
+/// tokio::spawn(server.serve("127.0.0.1:8080"));
+///
+/// config.reload();
+/// connections.broadcast("configChanged", &config)?;
+/// ```
+#[derive(Clone, Default)]
+pub struct Connections {
+    connections: Arc<Mutex<BTreeMap<u64, Connection>>>,
+}
+
+impl Connections {
+    pub(super) fn add(
+        &self,
+        context: Arc<Context>,
+        abort: AbortHandle,
+        close: oneshot::Sender<()>,
+    ) {
+        let connection_id = context.connection_id;
+        let connection = Connection {
+            context,
+            abort,
+            close,
+        };
+        self.connections
+            .lock()
+            .unwrap()
+            .insert(connection_id, connection);
+    }
+
+    pub(super) fn remove(&self, connection_id: u64) {
+        self.connections.lock().unwrap().remove(&connection_id);
+    }
+
+    /// Sends a notification to every connection and returns the number of connections it is
+    /// sent to.
+    pub fn broadcast<P: Serialize>(&self, method: &str, params: P) -> crate::Result<usize> {
+        let data = notification(method, params)?;
+        let connections = self.connections.lock().unwrap();
+        Ok(connections
+            .values()
+            .filter_map(|connection| connection.context.sender.as_ref())
+            .filter(|sender| sender.send(data.clone()).is_ok())
+            .count())
+    }
+
+    /// Sends a notification to the connection with the id.
+    pub fn send_to<P: Serialize>(
+        &self,
+        connection_id: u64,
+        method: &str,
+        params: P,
+    ) -> crate::Result<()> {
+        let data = notification(method, params)?;
+        let connections = self.connections.lock().unwrap();
+        let sender = connections
+            .get(&connection_id)
+            .and_then(|connection| connection.context.sender.as_ref())
+            .ok_or_else(|| format!("connection {} is not found", connection_id))?;
+        sender.send(data)?;
+        Ok(())
+    }
+
+    /// Returns the context of the connection with the id.
+    pub fn get(&self, connection_id: u64) -> Option<Arc<Context>> {
+        self.connections
+            .lock()
+            .unwrap()
+            .get(&connection_id)
+            .map(|connection| connection.context.clone())
+    }
+
+    /// Returns the contexts of the connections, ordered by their ids.
+    pub fn list(&self) -> Vec<Arc<Context>> {
+        self.connections
+            .lock()
+            .unwrap()
+            .values()
+            .map(|connection| connection.context.clone())
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.connections.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.connections.lock().unwrap().is_empty()
+    }
+
+    /// Closes the connection and returns whether it was live. The running calls are aborted,
+    /// except the `#[detached]` ones which run to the end but can not send anything to the
+    /// caller anymore, and the disconnect hook runs.
+    pub fn disconnect(&self, connection_id: u64) -> bool {
+        match self.connections.lock().unwrap().remove(&connection_id) {
+            Some(connection) => {
+                connection.abort.abort();
+                // The contexts that are still held keep the writer, so it is closed explicitly.
+                let _ = connection.close.send(());
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+fn notification<P: Serialize>(method: &str, params: P) -> crate::Result<Vec<u8>> {
+    let notification =
+        json_rpc::Request::new(String::from(method), serde_json::to_value(params)?, None);
+    Ok(serde_json::to_vec(&notification)?)
+}
@@ -0,0 +1,272 @@
+use {
+    super::{
+        cancellation::Running,
+        subscription::{Deferred, Subscriptions},
+    },
+    crate::{client::Client, json_rpc, peer::Peer, transport::Transport},
+    serde::Serialize,
+    serde_json::{Map, Value},
+    std::{
+        any::{Any, TypeId},
+        collections::HashMap,
+        fmt,
+        net::SocketAddr,
+        sync::{Arc, Mutex},
+        time::Instant,
+    },
+    tokio::sync::{mpsc, Semaphore},
+};
+
+/// Context of an RPC call. The fields of the connection are the same for every call of the
+/// connection, while the fields of the request are set for each call.
+#[derive(Clone)]
+pub struct Context {
+    pub caller_addr: SocketAddr,
+    /// Address of the server that accepted the connection
+    pub local_addr: SocketAddr,
+    /// Unique id of the connection in the server
+    pub connection_id: u64,
+    /// Time the connection was accepted
+    pub accepted_at: Instant,
+    /// Id of the request, `None` for notifications
+    pub request_id: Option<json_rpc::Id>,
+    /// Name of the called method
+    pub method: String,
+    /// `meta` member of the request, empty if the request does not have one
+    pub meta: Map<String, Value>,
+    /// Data that middlewares attach to the call
+    pub extensions: Extensions,
+    /// Data that lives as long as the connection
+    pub session: Session,
+    // Limits the concurrent blocking calls of the server
+    pub(super) blocking_permits: Option<Arc<Semaphore>>,
+    // Sends data to the writer of the connection
+    pub(super) sender: Option<mpsc::UnboundedSender<Vec<u8>>>,
+    // Subscriptions of the connection
+    pub(super) subscriptions: Subscriptions,
+    // Tasks that start after the response of the call
+    pub(super) deferred: Deferred,
+    // Calls of the connection that can be cancelled
+    pub(super) running: Running,
+    // Calls to the peer of the connection wait for their responses here
+    pub(super) transport: Arc<Transport>,
+    // Id of the connection in 'transport'
+    pub(super) peer_connection: u64,
+}
+
+impl Context {
+    /// Creates the context of a connection. The server fills in the rest of the fields.
+    pub fn new(caller_addr: SocketAddr) -> Self {
+        Self {
+            caller_addr,
+            local_addr: ([0, 0, 0, 0], 0).into(),
+            connection_id: 0,
+            accepted_at: Instant::now(),
+            request_id: None,
+            method: String::new(),
+            meta: Map::new(),
+            extensions: Extensions::default(),
+            session: Session::default(),
+            blocking_permits: None,
+            sender: None,
+            subscriptions: Subscriptions::default(),
+            deferred: Deferred::default(),
+            running: Running::default(),
+            transport: Arc::new(Transport::new()),
+            peer_connection: 0,
+        }
+    }
+
+    /// Sends a notification to the caller over the connection.
+    pub fn notify<P: Serialize>(&self, method: &str, params: P) -> crate::Result<()> {
+        let sender = self
+            .sender
+            .as_ref()
+            .ok_or_else(|| String::from("context does not have a connection"))?;
+        let notification =
+            json_rpc::Request::new(String::from(method), serde_json::to_value(params)?, None);
+        sender.send(serde_json::to_vec(&notification)?)?;
+        Ok(())
+    }
+
+    /// Returns the other side of the connection, whose methods can be called over the same
+    /// connection. `None` if the context does not have a connection. A plain
+    /// [Client](crate::client::Client) does not serve any methods, so calls to it fail with
+    /// `MethodNotFound`.
+    pub fn peer(&self) -> Option<Peer> {
+        let sender = self.sender.clone()?;
+        Some(Peer::new(Client::connected(
+            self.caller_addr,
+            self.peer_connection,
+            sender,
+            self.transport.clone(),
+        )))
+    }
+}
+
+/// A map of values keyed by their types, so middlewares can attach typed data to the call.
+/// Every call gets its own copy, so the values that are inserted in a call are not seen by the
+/// other calls. Data that is shared by the calls of a connection belongs to the [Session].
+///
+/// ```no_run
+/// struct User(String);
+///
+/// context.extensions.insert(User(String::from("cool")));
+/// let user = context.extensions.get::<User>();
+/// ```
+#[derive(Clone, Default)]
+pub struct Extensions {
+    map: HashMap<TypeId, Arc<dyn Any + Send + Sync>>,
+}
+
+impl Extensions {
+    /// Inserts a value, replacing the value of the same type.
+    pub fn insert<T: Send + Sync + 'static>(&mut self, value: T) {
+        self.map.insert(TypeId::of::<T>(), Arc::new(value));
+    }
+
+    pub fn get<T: Send + Sync + 'static>(&self) -> Option<&T> {
+        self.map
+            .get(&TypeId::of::<T>())
+            .and_then(|value| value.downcast_ref())
+    }
+
+    /// Removes the value of the type and returns whether there was one.
+    pub fn remove<T: Send + Sync + 'static>(&mut self) -> bool {
+        self.map.remove(&TypeId::of::<T>()).is_some()
+    }
+
+    pub fn contains<T: Send + Sync + 'static>(&self) -> bool {
+        self.map.contains_key(&TypeId::of::<T>())
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+}
+
+impl fmt::Debug for Extensions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Extensions")
+            .field("len", &self.map.len())
+            .finish()
+    }
+}
+
+/// Typed storage of a connection, shared by every call of the connection. Values are keyed by
+/// their types and they are dropped when the connection closes.
+///
+/// ```no_run
+/// struct Database(String);
+///
+/// fn select(context: Arc<Context>, name: String) {
+///     context.session.insert(Database(name));
+/// }
+///
+/// fn query(context: Arc<Context>) -> Option<String> {
+///     context.session.with(|db: &mut Database| db.0.clone())
+/// }
+/// ```
+#[derive(Clone, Default)]
+pub struct Session {
+    map: Arc<Mutex<HashMap<TypeId, Box<dyn Any + Send>>>>,
+}
+
+impl Session {
+    /// Inserts a value and returns the previous value of the same type.
+    pub fn insert<T: Send + 'static>(&self, value: T) -> Option<T> {
+        self.lock()
+            .insert(TypeId::of::<T>(), Box::new(value))
+            .and_then(|value| value.downcast().ok())
+            .map(|value| *value)
+    }
+
+    /// Returns a copy of the value of the type.
+    pub fn get<T: Clone + Send + 'static>(&self) -> Option<T> {
+        self.with(|value: &mut T| value.clone())
+    }
+
+    /// Calls `f` with the value of the type, if there is one. The session is locked until `f`
+    /// returns, so it should not block.
+    pub fn with<T: Send + 'static, R, F: FnOnce(&mut T) -> R>(&self, f: F) -> Option<R> {
+        self.lock()
+            .get_mut(&TypeId::of::<T>())
+            .and_then(|value| value.downcast_mut())
+            .map(f)
+    }
+
+    pub fn remove<T: Send + 'static>(&self) -> Option<T> {
+        self.lock()
+            .remove(&TypeId::of::<T>())
+            .and_then(|value| value.downcast().ok())
+            .map(|value| *value)
+    }
+
+    pub fn contains<T: Send + 'static>(&self) -> bool {
+        self.lock().contains_key(&TypeId::of::<T>())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<TypeId, Box<dyn Any + Send>>> {
+        // A panicking handler does not leave the map in an invalid state.
+        self.map.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl fmt::Debug for Session {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Session")
+            .field("len", &self.lock().len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct User(&'static str);
+
+    #[test]
+    fn extensions_are_keyed_by_type() {
+        let mut extensions = Extensions::default();
+        extensions.insert(User("cool"));
+        extensions.insert(3_u32);
+        extensions.insert(User("lib"));
+
+        assert_eq!(extensions.len(), 2);
+        assert_eq!(extensions.get::<User>(), Some(&User("lib")));
+        assert_eq!(extensions.get::<u32>(), Some(&3));
+        assert_eq!(extensions.get::<u64>(), None);
+
+        let clone = extensions.clone();
+        assert!(extensions.remove::<User>());
+        assert!(!extensions.contains::<User>());
+        assert!(clone.contains::<User>());
+    }
+
+    #[test]
+    fn session_is_shared_by_clones() {
+        let session = Session::default();
+        let clone = session.clone();
+        assert_eq!(session.insert(User("cool")), None);
+        assert_eq!(clone.insert(User("lib")), Some(User("cool")));
+
+        clone.insert(1_u32);
+        assert_eq!(
+            session.with(|count: &mut u32| {
+                *count += 1;
+                *count
+            }),
+            Some(2)
+        );
+        assert_eq!(clone.get::<u32>(), Some(2));
+        assert_eq!(session.remove::<User>(), Some(User("lib")));
+        assert!(!clone.contains::<User>());
+        assert_eq!(clone.with(|_: &mut User| ()), None);
+    }
+}
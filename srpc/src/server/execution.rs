@@ -0,0 +1,145 @@
+use {
+    crate::{json_rpc, transport::codec, BoxFuture},
+    std::{
+        collections::{HashMap, VecDeque},
+        sync::{Arc, Mutex},
+    },
+};
+
+/// Returns the ordering key of a request, see [ExecutionMode::ByKey].
+pub type OrderingKey = Arc<dyn Fn(&json_rpc::Request) -> Option<String> + Send + Sync>;
+
+/// How the requests of a connection are run. Requests that are serialized run one after another
+/// in the order they came, while the rest run concurrently. Requests of different connections
+/// always run concurrently.
+#[derive(Clone, Default)]
+pub enum ExecutionMode {
+    /// Every request runs in a task of its own. This is the default.
+    #[default]
+    Concurrent,
+    /// Every request runs after the previous request is answered.
+    Sequential,
+    /// Notifications run one after another, e.g. so that the state they set is not applied
+    /// in reverse. Calls run concurrently.
+    SequentialNotifications,
+    /// Requests with the same ordering key run one after another. Requests without a key run
+    /// concurrently.
+    ByKey(OrderingKey),
+}
+
+impl ExecutionMode {
+    /// Serializes the requests that share the key which the function returns.
+    ///
+    /// ```no_run
+    /// // Updates of the same document are applied in order.
+    /// server.set_execution_mode(ExecutionMode::by_key(|request| {
+    ///     request.params["uri"].as_str().map(String::from)
+    /// }));
+    /// ```
+    pub fn by_key<F>(key: F) -> Self
+    where
+        F: Fn(&json_rpc::Request) -> Option<String> + Send + Sync + 'static,
+    {
+        Self::ByKey(Arc::new(key))
+    }
+
+    /// Returns the lane of the request, `None` if it runs concurrently. A batch is serialized
+    /// as a whole, and only by the sequential modes.
+    pub(super) fn lane(&self, request: &codec::Type<json_rpc::Request>) -> Option<Lane> {
+        match (self, request) {
+            (Self::Concurrent, _) => None,
+            (Self::Sequential, _) => Some(Lane::All),
+            (Self::SequentialNotifications, codec::Type::Single(request)) => {
+                request.id.is_none().then_some(Lane::All)
+            }
+            (Self::SequentialNotifications, codec::Type::Batched(requests)) => {
+                let notifications = requests.iter().all(|request| request.id.is_none());
+                notifications.then_some(Lane::All)
+            }
+            (Self::ByKey(key), codec::Type::Single(request)) => key(request).map(Lane::Key),
+            (Self::ByKey(_), codec::Type::Batched(_)) => None,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub(super) enum Lane {
+    All,
+    Key(String),
+}
+
+/// Queues of the tasks of a connection which run one after another. A lane is in the map as
+/// long as its tasks are running.
+#[derive(Clone, Default)]
+pub(super) struct Lanes(Arc<Mutex<HashMap<Lane, VecDeque<BoxFuture<'static, ()>>>>>);
+
+impl Lanes {
+    /// Runs the task after the tasks that are pushed to the lane before.
+    pub fn push(&self, lane: Lane, task: BoxFuture<'static, ()>) {
+        let mut lanes = self.0.lock().unwrap();
+        match lanes.get_mut(&lane) {
+            Some(queue) => queue.push_back(task),
+            None => {
+                lanes.insert(lane.clone(), VecDeque::new());
+                tokio::spawn(self.clone().run(lane, task));
+            }
+        }
+    }
+
+    async fn run(self, lane: Lane, first: BoxFuture<'static, ()>) {
+        let mut next = Some(first);
+        while let Some(task) = next {
+            task.await;
+            let mut lanes = self.0.lock().unwrap();
+            next = lanes.get_mut(&lane).and_then(VecDeque::pop_front);
+            if next.is_none() {
+                lanes.remove(&lane);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        tokio::sync::{mpsc, oneshot},
+    };
+
+    #[tokio::test]
+    async fn lanes_run_in_order() {
+        let lanes = Lanes::default();
+        let (events, mut received) = mpsc::unbounded_channel();
+        let (unblock, blocked) = oneshot::channel::<()>();
+
+        let first = events.clone();
+        lanes.push(
+            Lane::All,
+            Box::pin(async move {
+                let _ = blocked.await;
+                let _ = first.send("first");
+            }),
+        );
+        let second = events.clone();
+        lanes.push(
+            Lane::All,
+            Box::pin(async move {
+                let _ = second.send("second");
+            }),
+        );
+        // Other lanes do not wait.
+        lanes.push(
+            Lane::Key(String::from("other")),
+            Box::pin(async move {
+                let _ = events.send("other");
+            }),
+        );
+
+        assert_eq!(received.recv().await.unwrap(), "other");
+        unblock.send(()).unwrap();
+        assert_eq!(received.recv().await.unwrap(), "first");
+        assert_eq!(received.recv().await.unwrap(), "second");
+        assert!(received.recv().await.is_none());
+        assert!(lanes.0.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,191 @@
+//! Middlewares of the server.
+//!
+//! A [Middleware] wraps every RPC call of the server. It sees the [Call] before the RPC method
+//! does, and decides whether to pass it to the rest of the chain with [Next::run]. So it can
+//! short-circuit with an error, change the call, or change the result on the way back.
+//! Middlewares run in the order they are added to the server, the first one being the
+//! outermost.
+//!
+//! # Example
+//! ```no_run
+//! use srpc::server::middleware::{BoxFuture, Call, Middleware, Next};
+//!
+//! struct Logger;
+//!
+//! impl Middleware for Logger {
+//!     fn handle<'a>(
+//!         &'a self,
+//!         call: Call,
+//!         next: Next<'a>,
+//!     ) -> BoxFuture<'a, Result<serde_json::Value, json_rpc::Error>> {
+//!         Box::pin(async move {
+//!             let method = call.method.clone();
+//!             let result = next.run(call).await;
+//!             log::info!("{} returned {:?}", method, result);
+//!             result
+//!         })
+//!     }
+//! }
+//!
+//! struct Auth;
+//!
+//! impl Middleware for Auth {
+//!     fn handle<'a>(
+//!         &'a self,
+//!         call: Call,
+//!         next: Next<'a>,
+//!     ) -> BoxFuture<'a, Result<serde_json::Value, json_rpc::Error>> {
+//!         Box::pin(async move {
+//!             if call.context.caller_addr.ip().is_loopback() {
+//!                 next.run(call).await
+//!             } else {
+//!                 Err(json_rpc::Error::custom(-32001, "Unauthorized", None))
+//!             }
+//!         })
+//!     }
+//! }
+//!
+//! let mut server = Server::new(StrService, StrService::caller);
+//! server.add_middleware(Logger);
+//! server.add_middleware(Auth);
+//! ```
+use {super::Context, crate::json_rpc, serde_json::Value};
+
+pub use crate::BoxFuture;
+
+/// An RPC call on its way to the RPC method.
+pub struct Call {
+    pub method: String,
+    pub params: Value,
+    /// Notifications don't have an id.
+    pub id: Option<json_rpc::Id>,
+    /// Context that the RPC method gets
+    pub context: Context,
+}
+
+pub trait Middleware: Send + Sync + 'static {
+    /// Handles the call, usually by passing it to `next`.
+    fn handle<'a>(
+        &'a self,
+        call: Call,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, Result<Value, json_rpc::Error>>;
+}
+
+/// Calls the RPC method of the call.
+pub(crate) type Endpoint<'a> =
+    dyn Fn(Call) -> BoxFuture<'a, Result<Value, json_rpc::Error>> + Send + Sync + 'a;
+
+/// The rest of the middleware chain, which ends with the RPC method.
+pub struct Next<'a> {
+    middlewares: &'a [Box<dyn Middleware>],
+    endpoint: &'a Endpoint<'a>,
+}
+
+impl<'a> Next<'a> {
+    pub(crate) fn new(middlewares: &'a [Box<dyn Middleware>], endpoint: &'a Endpoint<'a>) -> Self {
+        Self {
+            middlewares,
+            endpoint,
+        }
+    }
+
+    /// Passes the call to the next middleware, or to the RPC method if this is the end of
+    /// the chain.
+    pub async fn run(self, call: Call) -> Result<Value, json_rpc::Error> {
+        match self.middlewares.split_first() {
+            Some((middleware, rest)) => {
+                middleware
+                    .handle(call, Next::new(rest, self.endpoint))
+                    .await
+            }
+            None => (self.endpoint)(call).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        serde_json::json,
+        std::sync::{Arc, Mutex},
+    };
+
+    /// Records the order of the calls and appends its name to string results.
+    struct Tag {
+        name: &'static str,
+        log: Arc<Mutex<Vec<&'static str>>>,
+    }
+
+    impl Middleware for Tag {
+        fn handle<'a>(
+            &'a self,
+            call: Call,
+            next: Next<'a>,
+        ) -> BoxFuture<'a, Result<Value, json_rpc::Error>> {
+            Box::pin(async move {
+                self.log.lock().unwrap().push(self.name);
+                let result = next.run(call).await?;
+                Ok(json!(format!("{}{}", result.as_str().unwrap(), self.name)))
+            })
+        }
+    }
+
+    struct Deny;
+
+    impl Middleware for Deny {
+        fn handle<'a>(
+            &'a self,
+            call: Call,
+            next: Next<'a>,
+        ) -> BoxFuture<'a, Result<Value, json_rpc::Error>> {
+            Box::pin(async move {
+                if call.method == "denied" {
+                    Err(json_rpc::Error::custom(1, "Denied", None))
+                } else {
+                    next.run(call).await
+                }
+            })
+        }
+    }
+
+    fn call(method: &str) -> Call {
+        Call {
+            method: String::from(method),
+            params: Value::Null,
+            id: Some(json_rpc::Id::Num(1)),
+            context: Context::new(([127, 0, 0, 1], 8080).into()),
+        }
+    }
+
+    #[tokio::test]
+    async fn middlewares_compose_in_order() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let middlewares: Vec<Box<dyn Middleware>> = vec![
+            Box::new(Tag {
+                name: "a",
+                log: log.clone(),
+            }),
+            Box::new(Deny),
+            Box::new(Tag {
+                name: "b",
+                log: log.clone(),
+            }),
+        ];
+        let endpoint = |call: Call| -> BoxFuture<'_, Result<Value, json_rpc::Error>> {
+            Box::pin(async move { Ok(json!(call.method)) })
+        };
+
+        let result = Next::new(&middlewares, &endpoint).run(call("m")).await;
+        assert_eq!(result.unwrap(), json!("mba"));
+        assert_eq!(*log.lock().unwrap(), vec!["a", "b"]);
+
+        let error = Next::new(&middlewares, &endpoint)
+            .run(call("denied"))
+            .await
+            .unwrap_err();
+        assert_eq!(error.kind, json_rpc::ErrorKind::Custom(1));
+        assert_eq!(*log.lock().unwrap(), vec!["a", "b", "a"]);
+    }
+}
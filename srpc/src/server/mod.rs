@@ -0,0 +1,789 @@
+//! The async RPC server.
+//!
+//! Logic is simple. For each connection, server spawns a connection handler.
+//! And the connection handler spawns a request handler for each request. Request
+//! handlers run the corresponding RPC method and send back a Response(if the request
+//! is not a notification). Unless any error occurs related to connection, the
+//! server keeps the connection open.
+//!
+//! # Example
+//! ```no_run
+//! use srpc::server::Context;
+//! use srpc::server::Server;
+//! use std::sync::Arc;
+//!
+//! struct StrService;
+//!
+//! #[srpc::service]
+//! #[allow(unused)]
+//! impl StrService {
+//!    async fn contains(data: String, elem: String) -> bool {
+//!        data.contains(&elem)
+//!    }
+//!
+//!    async fn set_data(context: Arc<Context>, is_cool: bool) {
+//!        println!("Socket {:?}", context.caller_addr);
+//!        println!("Set a cool variable to: {}", is_cool);
+//!    }
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let server = Server::new(StrService, MyService::caller);
+//!     let _ = server.serve("127.0.0.1:8080").await;
+//! }
+//! ```
+//!
+//! RPC methods do not have to be `async`. Synchronous methods are called directly by the
+//! request handler, so they should be cheap. Methods that block, either because they are CPU
+//! heavy or do blocking IO, should be marked with `#[blocking]`. They are run on the blocking
+//! thread pool and [Server::set_max_blocking_calls] limits how many of them run at once.
+//! ```no_run
+//! #[blocking]
+//! fn hash(data: String) -> String { /* .. */ }
+//! ```
+//!
+//! # Reserved Parameters
+//!
+//! Services might want to use some local data which is not sent to the client. Currently,
+//! there are two reserved parameters which are passed to the service method if they present
+//! in the function parameters.
+//!
+//! ## Local server data
+//! Whenever shared server data is needed, this parameter can be used. SRPC passes the same data
+//! to every method so the data is not copied. Users do not pay the cost comes with `Arc` if they
+//! don't use the parameter. Either a plain reference or the `Arc` itself can be taken.
+//! ```no_run
+//! async fn foo(&self) {}
+//! async fn bar(self: Arc<Self>) {}
+//! ```
+//!
+//! ## Context of the connection
+//! Server might wanna know where the connection comes from. In that case `context: Arc<Context>`
+//! is used. [Context](struct.Context.html) contains the addresses, the id and the accept time of
+//! the connection, and the id and the method of the request.
+//! ```no_run
+//! async fn foo(context: Arc<Context>) {}
+//! ```
+//!
+//! The context of a call also contains the `meta` member of the request, which clients use
+//! to send data like auth tokens or trace ids alongside the params, and the
+//! [extensions](Extensions) that middlewares attach to the call.
+//! ```no_run
+//! async fn foo(context: Arc<Context>) {
+//!     let token = context.meta.get("token");
+//! }
+//! ```
+//!
+//! Handlers can send notifications to their caller with [Context::notify], e.g. to report the
+//! progress of a long call.
+//! ```no_run
+//! async fn import(context: Arc<Context>, rows: Vec<Row>) {
+//!     for (i, row) in rows.iter().enumerate() {
+//!         /* .. */
+//!         let _ = context.notify("progress", json!({ "done": i + 1 }));
+//!     }
+//! }
+//! ```
+//!
+//! Methods marked with `#[subscription]` return a stream. The caller gets the id of the
+//! subscription, and the items are sent to it as notifications until the stream ends, the
+//! caller unsubscribes or the connection is closed. See [subscribe].
+//! ```no_run
+//! #[subscription]
+//! fn new_heads(self: Arc<Self>) -> impl Stream<Item = Header> {
+//!     BroadcastStream::new(self.heads.subscribe()).filter_map(|head| async { head.ok() })
+//! }
+//! ```
+//!
+//! A call is aborted when the caller cancels it or closes the connection, so its response is
+//! not sent. Methods that must run to the end regardless are marked with `#[detached]`, see
+//! [detach].
+//! ```no_run
+//! #[detached]
+//! async fn save(self: Arc<Self>, document: Document) { /* .. */ }
+//! ```
+//!
+//! Requests of a connection run concurrently, so two notifications might be handled in the
+//! reverse order. [Server::set_execution_mode] runs all of the requests, only the
+//! notifications, or the requests that share a key one after another. See [ExecutionMode].
+//! ```no_run
+//! server.set_execution_mode(ExecutionMode::SequentialNotifications);
+//! ```
+//!
+//! Handlers can remember data about the caller between calls in the [Session] of the context,
+//! e.g. a logged in user. The session is shared by the calls of a connection and it is dropped
+//! when the connection closes.
+//! ```no_run
+//! struct User(String);
+//!
+//! fn login(context: Arc<Context>, name: String) {
+//!     context.session.insert(User(name));
+//! }
+//! ```
+//!
+//! # Middlewares
+//! Cross-cutting concerns like logging, authentication or rate limiting are implemented as
+//! [middlewares](middleware), which wrap every RPC call of the server.
+//!
+//! # Connection hooks
+//! [Server::on_connect] runs when a connection is accepted and it can refuse the connection,
+//! e.g. by the address of the caller. [Server::on_disconnect] runs when the connection closes,
+//! e.g. to release what the caller held.
+//! ```no_run
+//! server.on_connect(|context| !banned.contains(&context.caller_addr.ip()));
+//! server.on_disconnect(|context| locks.release(context.connection_id));
+//! ```
+//!
+//! [Server::connections] keeps track of the live connections, so the server can notify every
+//! caller, e.g. before it shuts down, or disconnect a caller. See [Connections].
+//!
+//! # Peers
+//! If the caller connects with [Peer::connect](crate::peer::Peer::connect), it serves methods
+//! too, and handlers call them through [Context::peer]. See [peer](crate::peer).
+//!
+//!
+mod cancellation;
+mod connections;
+mod context;
+mod execution;
+pub mod middleware;
+mod subscription;
+
+pub use {
+    connections::Connections,
+    context::{Context, Extensions, Session},
+    execution::{ExecutionMode, OrderingKey},
+    subscription::subscribe,
+};
+
+use {
+    super::transport::Transport,
+    crate::{
+        json_rpc,
+        peer::Peer,
+        transport::{codec, Reader},
+    },
+    execution::Lanes,
+    futures::{
+        future::{AbortHandle, Abortable},
+        stream::StreamExt,
+    },
+    middleware::{BoxFuture, Call, Middleware, Next},
+    std::{future::Future, net::SocketAddr, pin::Pin, sync::Arc},
+    subscription::Deferred,
+    tokio::{
+        io::{self, ReadHalf, WriteHalf},
+        net::{TcpListener, TcpStream, ToSocketAddrs},
+        sync::{mpsc, oneshot, Semaphore},
+    },
+};
+
+// Hooks that run when a connection is accepted and closed
+type ConnectHook = Box<dyn Fn(&Context) -> bool + Send + Sync>;
+type DisconnectHook = Box<dyn Fn(&Context) + Send + Sync>;
+
+// An async function which returns an srpc::Result
+type ServiceCall<T> =
+    fn(
+        Arc<T>,
+        Arc<Context>,
+        String,
+        serde_json::Value,
+    ) -> Pin<Box<dyn Future<Output = Result<serde_json::Value, json_rpc::Error>> + Send>>;
+
+/// Runs a blocking RPC method on the blocking thread pool. If the server limits the number of
+/// concurrent blocking calls, this waits until a slot is free.
+///
+/// Methods marked with `#[blocking]` in `#[srpc::service]` are called through this function.
+pub async fn spawn_blocking<F, R>(context: Arc<Context>, f: F) -> Result<R, json_rpc::Error>
+where
+    F: FnOnce() -> R + Send + 'static,
+    R: Send + 'static,
+{
+    let permit = match &context.blocking_permits {
+        Some(permits) => Some(permits.clone().acquire_owned().await.map_err(|e| {
+            json_rpc::Error::new(
+                json_rpc::ErrorKind::InternalError,
+                Some(serde_json::Value::from(e.to_string())),
+            )
+        })?),
+        None => None,
+    };
+
+    tokio::task::spawn_blocking(move || {
+        // The permit is released when the method returns, even if the caller is gone.
+        let _permit = permit;
+        f()
+    })
+    .await
+    .map_err(|e| {
+        json_rpc::Error::new(
+            json_rpc::ErrorKind::InternalError,
+            Some(serde_json::Value::from(e.to_string())),
+        )
+    })
+}
+
+/// Runs an RPC method in a task of its own, so it runs to the end even if the call is
+/// cancelled or the connection is closed. Its result is dropped in that case.
+///
+/// Methods marked with `#[detached]` in `#[srpc::service]` are called through this function.
+pub async fn detach<F, R>(future: F) -> Result<R, json_rpc::Error>
+where
+    F: Future<Output = Result<R, json_rpc::Error>> + Send + 'static,
+    R: Send + 'static,
+{
+    tokio::spawn(future).await.map_err(|e| {
+        json_rpc::Error::new(
+            json_rpc::ErrorKind::InternalError,
+            Some(serde_json::Value::from(e.to_string())),
+        )
+    })?
+}
+
+pub struct Server<T> {
+    service: Arc<T>,
+    service_call: ServiceCall<T>,
+    transport: Arc<Transport>,
+    blocking_permits: Option<Arc<Semaphore>>,
+    middlewares: Vec<Box<dyn Middleware>>,
+    on_connect: Option<ConnectHook>,
+    on_disconnect: Option<DisconnectHook>,
+    connections: Connections,
+    execution_mode: ExecutionMode,
+    #[cfg(feature = "validation")]
+    validator: Option<crate::openrpc::validation::Validator>,
+}
+
+impl<T> Server<T>
+where
+    T: 'static + Send + Sync,
+{
+    pub fn new(service: T, service_call: ServiceCall<T>) -> Self {
+        Self {
+            service: Arc::new(service),
+            service_call,
+            transport: Arc::new(Transport::new()),
+            blocking_permits: None,
+            middlewares: Vec::new(),
+            on_connect: None,
+            on_disconnect: None,
+            connections: Connections::default(),
+            execution_mode: ExecutionMode::default(),
+            #[cfg(feature = "validation")]
+            validator: None,
+        }
+    }
+
+    /// Limits the number of `#[blocking]` methods running at the same time. Additional calls
+    /// wait until one of the running calls finishes. There is no limit by default.
+    pub fn set_max_blocking_calls(&mut self, limit: usize) {
+        self.blocking_permits = Some(Arc::new(Semaphore::new(limit)));
+    }
+
+    /// Sets which requests of a connection run one after another. By default, every request
+    /// runs concurrently.
+    pub fn set_execution_mode(&mut self, mode: ExecutionMode) {
+        self.execution_mode = mode;
+    }
+
+    pub fn set_service(&mut self, service: T, service_call: ServiceCall<T>) {
+        self.service = Arc::new(service);
+        self.service_call = service_call;
+    }
+
+    /// Validates the params of each request before the RPC method is called. Requests with
+    /// invalid params get an `InvalidParams` error listing every violation.
+    #[cfg(feature = "validation")]
+    pub fn set_validator(&mut self, validator: crate::openrpc::validation::Validator) {
+        self.validator = Some(validator);
+    }
+
+    /// Adds a middleware which wraps every RPC call. Middlewares run in the order they are
+    /// added.
+    pub fn add_middleware<M: Middleware>(&mut self, middleware: M) {
+        self.middlewares.push(Box::new(middleware));
+    }
+
+    /// Sets a hook which runs when a connection is accepted, before any request is read. The
+    /// connection is closed if the hook returns false.
+    pub fn on_connect<F: Fn(&Context) -> bool + Send + Sync + 'static>(&mut self, hook: F) {
+        self.on_connect = Some(Box::new(hook));
+    }
+
+    /// Sets a hook which runs when a connection is closed. Calls of the connection that are
+    /// still running are not waited for.
+    pub fn on_disconnect<F: Fn(&Context) + Send + Sync + 'static>(&mut self, hook: F) {
+        self.on_disconnect = Some(Box::new(hook));
+    }
+
+    /// Returns the live connections of the server, which can be used to notify or disconnect
+    /// the callers. Since [Server::serve] takes the server, the connections are taken before.
+    pub fn connections(&self) -> Connections {
+        self.connections.clone()
+    }
+
+    /// Calls the RPC method of the request through the middlewares. Tasks that the method
+    /// defers are added to 'deferred'.
+    async fn call(
+        self: &Arc<Self>,
+        context: Arc<Context>,
+        request: json_rpc::Request,
+        deferred: &Deferred,
+    ) -> Result<serde_json::Value, json_rpc::Error> {
+        let context = Context {
+            request_id: request.id.clone(),
+            method: request.method.clone(),
+            meta: request.meta.unwrap_or_default(),
+            deferred: deferred.clone(),
+            ..Context::clone(&context)
+        };
+        let call = Call {
+            method: request.method,
+            params: request.params,
+            id: request.id,
+            context,
+        };
+        let endpoint = |call: Call| -> BoxFuture<'_, Result<serde_json::Value, json_rpc::Error>> {
+            if call.method == json_rpc::subscription::UNSUBSCRIBE {
+                return Box::pin(async move { Self::unsubscribe(call) });
+            }
+
+            #[cfg(feature = "validation")]
+            if let Some(validator) = &self.validator {
+                if let Err(err) = validator.validate(&call.method, &call.params) {
+                    return Box::pin(async move { Err(err) });
+                }
+            }
+
+            (self.service_call)(
+                self.service.clone(),
+                Arc::new(call.context),
+                call.method,
+                call.params,
+            )
+        };
+
+        Next::new(&self.middlewares, &endpoint).run(call).await
+    }
+
+    /// Stops a subscription of the caller, returns whether it was running.
+    fn unsubscribe(call: Call) -> Result<serde_json::Value, json_rpc::Error> {
+        let params: json_rpc::subscription::SubscriptionParams =
+            serde_json::from_value(call.params).map_err(|e| {
+                json_rpc::Error::new(
+                    json_rpc::ErrorKind::InvalidParams,
+                    Some(serde_json::Value::from(e.to_string())),
+                )
+            })?;
+        Ok(serde_json::Value::from(
+            call.context.subscriptions.cancel(params.subscription),
+        ))
+    }
+
+    /// Calls the corresponding rpc method and sends the result via sender. If the request is a
+    /// notification, no data is sent back.
+    async fn handle_single_request(
+        self: &Arc<Self>,
+        context: Arc<Context>,
+        request: json_rpc::Request,
+        sender: mpsc::UnboundedSender<Vec<u8>>,
+    ) {
+        let deferred = Deferred::default();
+        if let Some(id) = request.id.clone() {
+            let response: Vec<u8> = match self.call(context, request, &deferred).await {
+                Ok(result) => json_rpc::Response::from_result(result, id),
+                Err(err) => json_rpc::Response::from_error(err, id),
+            }
+            .into();
+
+            if response.len() > std::u32::MAX as usize {
+                panic!("maximum response size is exceeded");
+            }
+            // TODO: Error handling
+            let _ = sender.send(response);
+        } else {
+            // We don't need to see the result of a notification
+            let _ = self.call(context, request, &deferred).await;
+        }
+        deferred.spawn();
+    }
+
+    /// Calls the corresponding rpc method for each request and sends the results of each
+    /// non-notification RPC call at once, as an array. It sends back an empty array, if all requests are
+    /// notifications.
+    async fn handle_batched_request(
+        self: &Arc<Self>,
+        context: Arc<Context>,
+        requests: Vec<json_rpc::Request>,
+        sender: mpsc::UnboundedSender<Vec<u8>>,
+    ) {
+        let deferred = Deferred::default();
+        let mut response = vec![b'['];
+        for request in requests {
+            // Is it a notification?
+            if let Some(id) = request.id.clone() {
+                let value: Vec<u8> = match self.call(context.clone(), request, &deferred).await {
+                    Ok(result) => json_rpc::Response::from_result(result, id),
+                    Err(err) => json_rpc::Response::from_error(err, id),
+                }
+                .into();
+                response.extend(value);
+                response.push(b',');
+            } else {
+                // We don't need the result of a notification
+                let _ = self.call(context.clone(), request, &deferred).await;
+            }
+        }
+        if response.len() != 1 {
+            if response.len() > u32::MAX as usize {
+                panic!("maximum response size is exceeded");
+            }
+            response.pop();
+        }
+        response.push(b']');
+
+        let _ = sender.send(response);
+        deferred.spawn();
+    }
+
+    /// Calls the appropriate request handler
+    ///
+    /// If an RPC error occurs, the error is sent in the 'error' field of the response and the
+    /// 'result' field is set to 'None'.
+    /// Otherwise the 'error' field is set to 'None' and the 'result' field contains the return
+    /// value of the RPC method.
+    async fn handle_request(
+        self: Arc<Self>,
+        context: Arc<Context>,
+        request: codec::Type<json_rpc::Request>,
+        sender: mpsc::UnboundedSender<Vec<u8>>,
+    ) {
+        match request {
+            codec::Type::Single(request) => {
+                self.handle_single_request(context, request, sender).await
+            }
+            codec::Type::Batched(requests) => {
+                self.handle_batched_request(context, requests, sender).await
+            }
+        };
+    }
+
+    fn is_cancel(request: &json_rpc::Request) -> bool {
+        request.id.is_none() && request.method == json_rpc::cancel::CANCEL_REQUEST
+    }
+
+    /// Passes the responses and the subscription items in the message to the calls that the
+    /// server made to the peer, aborts the cancelled calls and returns the requests for the
+    /// service.
+    fn route_message(
+        context: &Context,
+        message: codec::Type<json_rpc::Message>,
+    ) -> Option<codec::Type<json_rpc::Request>> {
+        match message {
+            codec::Type::Single(json_rpc::Message::Request(request)) => {
+                if Transport::is_subscription(&request) {
+                    context
+                        .transport
+                        .handle_subscription(context.peer_connection, request);
+                    None
+                } else if Self::is_cancel(&request) {
+                    context.running.cancel(request);
+                    None
+                } else {
+                    Some(codec::Type::Single(request))
+                }
+            }
+            codec::Type::Single(json_rpc::Message::Response(response)) => {
+                context
+                    .transport
+                    .handle_response(context.peer_connection, response);
+                None
+            }
+            codec::Type::Batched(messages) => {
+                let mut requests = Vec::new();
+                for message in messages {
+                    match message {
+                        json_rpc::Message::Request(request)
+                            if Transport::is_subscription(&request) =>
+                        {
+                            context
+                                .transport
+                                .handle_subscription(context.peer_connection, request)
+                        }
+                        json_rpc::Message::Request(request) if Self::is_cancel(&request) => {
+                            context.running.cancel(request)
+                        }
+                        json_rpc::Message::Request(request) => requests.push(request),
+                        json_rpc::Message::Response(response) => {
+                            context
+                                .transport
+                                .handle_response(context.peer_connection, response);
+                        }
+                    }
+                }
+                // Nothing is sent back for a batch without requests, e.g. only cancellations.
+                if requests.is_empty() {
+                    None
+                } else {
+                    Some(codec::Type::Batched(requests))
+                }
+            }
+        }
+    }
+
+    /// Spawns an IO writer for the connection and runs the connect hook. Returns the context of
+    /// the connection with the signal that closes the writer, or `None` if the hook refuses the
+    /// connection.
+    fn open_connection(
+        &self,
+        writer: WriteHalf<TcpStream>,
+        context: Context,
+    ) -> Option<(Context, oneshot::Sender<()>)> {
+        let (sender, close) = self.transport.spawn_closable_writer(writer);
+        let context = Context {
+            sender: Some(sender),
+            peer_connection: context.transport.open_connection(),
+            ..context
+        };
+
+        if let Some(on_connect) = &self.on_connect {
+            if !on_connect(&context) {
+                log::debug!("Refused the connection from {}", context.caller_addr);
+                return None;
+            }
+        }
+        Some((context, close))
+    }
+
+    /// Reads the connection and spawns new tasks as new requests come. Responses are passed to
+    /// the calls that the server made to the peer.
+    async fn handle_connection(
+        self: Arc<Self>,
+        reader: ReadHalf<TcpStream>,
+        context: Context,
+        close: oneshot::Sender<()>,
+    ) {
+        log::debug!("Handling the connection from {}", context.caller_addr);
+        let sender = match &context.sender {
+            Some(sender) => sender.clone(),
+            None => return,
+        };
+        let mut reader: Reader<json_rpc::Message, _> = Reader::new(reader);
+        let context = Arc::new(context);
+        let (abort, registration) = AbortHandle::new_pair();
+        self.connections.add(context.clone(), abort, close);
+        let lanes = Lanes::default();
+
+        let read = async {
+            loop {
+                match reader.next().await {
+                    Some(Ok(message)) => {
+                        let request = match Self::route_message(&context, message) {
+                            Some(request) => request,
+                            None => continue,
+                        };
+                        let request_id = match &request {
+                            codec::Type::Single(request) => request.id.clone(),
+                            codec::Type::Batched(_) => None,
+                        };
+                        let lane = self.execution_mode.lane(&request);
+                        let handle = Self::handle_request(
+                            self.clone(),
+                            context.clone(),
+                            request,
+                            sender.clone(),
+                        );
+                        // The task is registered before the next message is read, so the
+                        // cancel notification that follows the call finds it.
+                        let (key, registration) = context.running.start(request_id.clone());
+                        let context_clone = context.clone();
+                        let task = async move {
+                            if Abortable::new(handle, registration).await.is_ok() {
+                                context_clone.running.finish(key, request_id.as_ref());
+                            }
+                        };
+                        match lane {
+                            Some(lane) => lanes.push(lane, Box::pin(task)),
+                            None => {
+                                tokio::spawn(task);
+                            }
+                        }
+                    }
+                    Some(Err(e)) => {
+                        log::error!("Error occured during handling connection: {}", e);
+                        break;
+                    }
+                    None => break,
+                }
+            }
+        };
+        // The reading is aborted when the connection is disconnected from the registry.
+        let _ = Abortable::new(read, registration).await;
+
+        self.connections.remove(context.connection_id);
+        // Nobody waits for the responses of the running calls anymore.
+        context.running.cancel_all();
+        context.subscriptions.cancel_all();
+        // The calls to the peer are not answered anymore.
+        context.transport.close_connection(context.peer_connection);
+        if let Some(on_disconnect) = &self.on_disconnect {
+            on_disconnect(&context);
+        }
+    }
+
+    /// Serves services from a TcpStream.
+    /// When a new connection is accepted, it spawns a task to handle that connection.
+    ///
+    /// TODO: Server is limited to TcpStream right now. It should be able to serve
+    ///       anything that implements Stream trait
+    pub async fn serve<A: ToSocketAddrs>(self, addr: A) -> crate::Result<()> {
+        self.serve_listener(TcpListener::bind(addr).await?).await
+    }
+
+    /// Serves services from a listener which is already bound, e.g. to port 0 so that the
+    /// address is picked by the OS and read with `listener.local_addr()` before serving.
+    /// Connections are queued from the moment the listener is bound, so clients can connect
+    /// before this is polled.
+    ///
+    /// ```no_run
+    /// let listener = TcpListener::bind("127.0.0.1:0").await?;
+    /// let addr = listener.local_addr()?;
+    /// tokio::spawn(server.serve_listener(listener));
+    /// ```
+    pub async fn serve_listener(self, listener: TcpListener) -> crate::Result<()> {
+        let arc_self = Arc::new(self);
+        let mut connection_id = 0;
+        loop {
+            let (stream, addr) = listener.accept().await?;
+            connection_id += 1;
+            let context = Context {
+                local_addr: stream.local_addr()?,
+                connection_id,
+                blocking_permits: arc_self.blocking_permits.clone(),
+                ..Context::new(addr)
+            };
+            let (reader, writer) = io::split(stream);
+            if let Some((context, close)) = arc_self.open_connection(writer, context) {
+                tokio::spawn(arc_self.clone().handle_connection(reader, context, close));
+            }
+        }
+    }
+
+    /// Connects to the server at the address and serves the service on the connection, while
+    /// the returned peer calls the methods of the server.
+    pub(crate) async fn connect(self: Arc<Self>, addr: SocketAddr) -> crate::Result<Peer> {
+        let stream = TcpStream::connect(addr).await?;
+        let context = Context {
+            local_addr: stream.local_addr()?,
+            connection_id: 1,
+            blocking_permits: self.blocking_permits.clone(),
+            ..Context::new(addr)
+        };
+        let (reader, writer) = io::split(stream);
+        let (context, close) = self
+            .open_connection(writer, context)
+            .ok_or_else(|| String::from("the connection is refused by on_connect"))?;
+        let peer = context.peer().expect("the connection is open");
+        tokio::spawn(self.handle_connection(reader, context, close));
+        Ok(peer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::client::Client,
+        serde_json::json,
+        std::{
+            sync::atomic::{AtomicUsize, Ordering},
+            thread,
+            time::Duration,
+        },
+    };
+
+    fn echo(
+        _: Arc<()>,
+        _: Arc<Context>,
+        _: String,
+        params: serde_json::Value,
+    ) -> Pin<Box<dyn Future<Output = Result<serde_json::Value, json_rpc::Error>> + Send>> {
+        Box::pin(async move { Ok(params) })
+    }
+
+    #[test]
+    fn batched_subscription_items_go_to_peer() {
+        let mut context = Context::new(([127, 0, 0, 1], 8080).into());
+        context.peer_connection = context.transport.open_connection();
+        let (items, mut received) = tokio::sync::mpsc::unbounded_channel();
+        let id = json_rpc::Id::Num(1);
+        let connection = context.peer_connection;
+        context
+            .transport
+            .add_subscription(connection, id.clone(), items);
+        context
+            .transport
+            .handle_response(connection, json_rpc::Response::from_result(json!(7), id));
+
+        let item = json_rpc::Request::new(
+            String::from(json_rpc::subscription::SUBSCRIPTION),
+            json!({ "subscription": 7, "result": "cool" }),
+            None,
+        );
+        let batch = codec::Type::Batched(vec![json_rpc::Message::Request(item)]);
+        assert!(Server::<()>::route_message(&context, batch).is_none());
+        assert_eq!(received.try_recv().unwrap(), json!("cool"));
+    }
+
+    #[test]
+    fn batch_of_cancellations_has_no_response() {
+        let context = Context::new(([127, 0, 0, 1], 8080).into());
+        let cancel = json_rpc::Request::new(
+            String::from(json_rpc::cancel::CANCEL_REQUEST),
+            json!({ "id": 1 }),
+            None,
+        );
+        let batch = codec::Type::Batched(vec![json_rpc::Message::Request(cancel)]);
+        assert!(Server::<()>::route_message(&context, batch).is_none());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn serves_bound_listener() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(Server::new((), echo).serve_listener(listener));
+
+        let client = Client::new(addr, Arc::new(Transport::new()));
+        let request = json_rpc::Request::new(String::from("echo"), json!([3]), None);
+        let response = client.call(request).await.unwrap();
+        assert_eq!(response.result, Some(json!([3])));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn blocking_calls_are_limited() {
+        let context = Arc::new(Context {
+            blocking_permits: Some(Arc::new(Semaphore::new(2))),
+            ..Context::new(([127, 0, 0, 1], 8080).into())
+        });
+        let running = Arc::new(AtomicUsize::new(0));
+        let max_running = Arc::new(AtomicUsize::new(0));
+
+        let calls = (0..6).map(|_| {
+            let running = running.clone();
+            let max_running = max_running.clone();
+            spawn_blocking(context.clone(), move || {
+                let now_running = running.fetch_add(1, Ordering::SeqCst) + 1;
+                max_running.fetch_max(now_running, Ordering::SeqCst);
+                thread::sleep(Duration::from_millis(20));
+                running.fetch_sub(1, Ordering::SeqCst);
+            })
+        });
+        for result in futures::future::join_all(calls).await {
+            result.unwrap();
+        }
+
+        assert_eq!(max_running.load(Ordering::SeqCst), 2);
+    }
+}
@@ -0,0 +1,191 @@
+use {
+    super::Context,
+    crate::{
+        json_rpc::{self, subscription::*},
+        BoxFuture,
+    },
+    futures::{
+        future::{AbortHandle, Abortable},
+        Stream, StreamExt,
+    },
+    serde::Serialize,
+    serde_json::Value,
+    std::{
+        collections::HashMap,
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            Arc, Mutex,
+        },
+    },
+};
+
+/// Tasks of a call that start after its response is queued, so the caller gets the response
+/// before anything the tasks send.
+#[derive(Clone, Default)]
+pub(super) struct Deferred(Arc<Mutex<Tasks>>);
+
+impl Deferred {
+    /// Defers the task. `cancel` runs instead if the task is never spawned, e.g. because the
+    /// call is aborted.
+    fn push<F: FnOnce() + Send + 'static>(&self, task: BoxFuture<'static, ()>, cancel: F) {
+        self.0.lock().unwrap().0.push((task, Box::new(cancel)));
+    }
+
+    /// Spawns the tasks that are deferred so far.
+    pub fn spawn(&self) {
+        for (task, _) in std::mem::take(&mut self.0.lock().unwrap().0) {
+            tokio::spawn(task);
+        }
+    }
+}
+
+// A deferred task and what runs instead if it is never spawned
+type Task = (BoxFuture<'static, ()>, Box<dyn FnOnce() + Send>);
+
+#[derive(Default)]
+struct Tasks(Vec<Task>);
+
+impl Drop for Tasks {
+    fn drop(&mut self) {
+        for (_, cancel) in self.0.drain(..) {
+            cancel();
+        }
+    }
+}
+
+/// Running subscriptions of a connection.
+#[derive(Clone, Default)]
+pub(super) struct Subscriptions {
+    last_id: Arc<AtomicU64>,
+    running: Arc<Mutex<HashMap<u64, AbortHandle>>>,
+}
+
+impl Subscriptions {
+    fn add(&self, handle: AbortHandle) -> u64 {
+        let id = self.last_id.fetch_add(1, Ordering::Relaxed) + 1;
+        self.running.lock().unwrap().insert(id, handle);
+        id
+    }
+
+    /// Stops the subscription and returns whether it was running.
+    pub fn cancel(&self, id: u64) -> bool {
+        match self.running.lock().unwrap().remove(&id) {
+            Some(handle) => {
+                handle.abort();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Stops every subscription, e.g. when the connection is closed.
+    pub fn cancel_all(&self) {
+        for (_, handle) in self.running.lock().unwrap().drain() {
+            handle.abort();
+        }
+    }
+
+    fn finish(&self, id: u64) {
+        self.running.lock().unwrap().remove(&id);
+    }
+}
+
+/// Sends the items of the stream to the caller as notifications, and returns the id of the
+/// subscription which is sent back as the result of the call. The items are sent after the
+/// response, until the stream ends, the caller unsubscribes or the connection is closed.
+///
+/// Subscriptions that are called as notifications are rejected, since the caller could not
+/// learn the id of the subscription.
+///
+/// Methods marked with `#[subscription]` in `#[srpc::service]` are called through this
+/// function.
+pub fn subscribe<S>(context: &Context, stream: S) -> Result<Value, json_rpc::Error>
+where
+    S: Stream + Send + 'static,
+    S::Item: Serialize,
+{
+    if context.request_id.is_none() {
+        return Err(json_rpc::Error::new(
+            json_rpc::ErrorKind::InvalidRequest,
+            Some(Value::from("subscriptions can not be notifications")),
+        ));
+    }
+    let sender = context.sender.clone().ok_or_else(|| {
+        json_rpc::Error::new(
+            json_rpc::ErrorKind::InternalError,
+            Some(Value::from("subscriptions need a connection")),
+        )
+    })?;
+    let subscriptions = context.subscriptions.clone();
+    let (handle, registration) = AbortHandle::new_pair();
+    let id = subscriptions.add(handle);
+
+    let notify = move |method: &str, result: Option<Value>| {
+        let params = SubscriptionParams {
+            subscription: id,
+            result,
+        };
+        let notification = json_rpc::Request::new(
+            String::from(method),
+            serde_json::to_value(params).unwrap(),
+            None,
+        );
+        sender.send(serde_json::to_vec(&notification).unwrap())
+    };
+    let running = subscriptions.clone();
+    let forward = async move {
+        let mut stream = Box::pin(stream);
+        while let Some(item) = stream.next().await {
+            if notify(SUBSCRIPTION, Some(serde_json::to_value(item).unwrap())).is_err() {
+                break;
+            }
+        }
+        subscriptions.finish(id);
+        let _ = notify(SUBSCRIPTION_END, None);
+    };
+    context.deferred.push(
+        Box::pin(async move {
+            let _ = Abortable::new(forward, registration).await;
+        }),
+        move || running.finish(id),
+    );
+
+    Ok(Value::from(id))
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, tokio::sync::mpsc};
+
+    fn context() -> Context {
+        let (sender, _) = mpsc::unbounded_channel();
+        Context {
+            request_id: Some(json_rpc::Id::Num(1)),
+            sender: Some(sender),
+            ..Context::new(([127, 0, 0, 1], 0).into())
+        }
+    }
+
+    #[test]
+    fn subscription_is_removed_if_never_spawned() {
+        let context = context();
+        let subscriptions = context.subscriptions.clone();
+        subscribe(&context, futures::stream::iter(vec![1, 2])).unwrap();
+        assert_eq!(subscriptions.running.lock().unwrap().len(), 1);
+
+        // The call is aborted before its deferred tasks are spawned.
+        drop(context);
+        assert!(subscriptions.running.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn notifications_can_not_subscribe() {
+        let context = Context {
+            request_id: None,
+            ..context()
+        };
+        let error = subscribe(&context, futures::stream::iter(vec![1, 2])).unwrap_err();
+        assert_eq!(error.kind, json_rpc::ErrorKind::InvalidRequest);
+        assert!(context.subscriptions.running.lock().unwrap().is_empty());
+    }
+}
@@ -1,15 +1,24 @@
 ///
 /// Transport handles the IO operations for both the client and the server.
 ///
-/// TODO: We need some sort of garbage collection in here. Note that when a read operation
-/// fails, we keep storing the receiver.
+/// The calls and subscriptions waiting on a connection are kept per connection, and they are
+/// dropped when the reader of the connection stops, so the calls fail and the subscription
+/// streams end.
 use {
     super::{codec, json_rpc, Reader},
-    futures::StreamExt,
+    futures::{
+        future::{self, Either},
+        StreamExt,
+    },
+    json_rpc::subscription::{SubscriptionParams, SUBSCRIPTION, SUBSCRIPTION_END},
     log::{error, info, warn},
+    serde_json::Value,
     std::{
         collections::HashMap,
-        sync::{Arc, Mutex},
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            Arc, Mutex,
+        },
     },
     tokio::{
         io::{AsyncWriteExt, ReadHalf, WriteHalf},
@@ -19,56 +28,276 @@ use {
 };
 
 type Receivers = HashMap<json_rpc::Id, oneshot::Sender<json_rpc::Response>>;
+// Handlers of the incoming notifications by method. A handler is removed when it returns false.
+type NotificationHandler = Box<dyn Fn(&json_rpc::Request) -> bool + Send>;
+type NotificationHandlers = HashMap<String, Vec<NotificationHandler>>;
+
+/// Calls and subscriptions that wait on a connection.
+#[derive(Default)]
+struct Connection {
+    receivers: Receivers,
+    // Subscription calls that are not answered yet, by request id
+    pending: HashMap<json_rpc::Id, mpsc::UnboundedSender<Value>>,
+    // By subscription id, which the other side assigns per connection
+    running: HashMap<u64, mpsc::UnboundedSender<Value>>,
+}
 
 pub struct Transport {
-    receivers: Arc<Mutex<Receivers>>,
+    // Open connections by their ids in the transport
+    connections: Arc<Mutex<HashMap<u64, Connection>>>,
+    last_connection: AtomicU64,
+    notification_handlers: Arc<Mutex<NotificationHandlers>>,
 }
 
 impl Transport {
     pub fn new() -> Self {
         Self {
-            receivers: Arc::new(Mutex::new(HashMap::new())),
+            connections: Arc::new(Mutex::new(HashMap::new())),
+            last_connection: AtomicU64::new(0),
+            notification_handlers: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
-    pub fn spawn_reader(self: &Arc<Self>, reader: ReadHalf<TcpStream>) {
-        let receivers = self.receivers.clone();
-        tokio::spawn(Transport::reader(receivers, reader));
+    /// Spawns the reader of a connection and returns the id of the connection in the
+    /// transport. Requests that come from the other side are answered through `writer` with a
+    /// `MethodNotFound` error.
+    pub fn spawn_reader(
+        self: &Arc<Self>,
+        reader: ReadHalf<TcpStream>,
+        writer: mpsc::UnboundedSender<Vec<u8>>,
+    ) -> u64 {
+        let connection = self.open_connection();
+        tokio::spawn(self.clone().reader(connection, reader, writer));
+        connection
+    }
+
+    /// Starts keeping the calls of a new connection and returns its id.
+    pub(crate) fn open_connection(&self) -> u64 {
+        let connection = self.last_connection.fetch_add(1, Ordering::Relaxed) + 1;
+        self.connections
+            .lock()
+            .unwrap()
+            .insert(connection, Connection::default());
+        connection
+    }
+
+    /// Drops the calls and the subscriptions of the connection, so the calls fail and the
+    /// subscription streams end.
+    pub(crate) fn close_connection(&self, connection: u64) {
+        self.connections.lock().unwrap().remove(&connection);
     }
 
-    pub fn spawn_writer(self: &Arc<Self>, writer: WriteHalf<TcpStream>) -> mpsc::UnboundedSender<Vec<u8>> {
+    pub fn spawn_writer(
+        self: &Arc<Self>,
+        writer: WriteHalf<TcpStream>,
+    ) -> mpsc::UnboundedSender<Vec<u8>> {
+        self.spawn_closable_writer(writer).0
+    }
+
+    /// Spawns a writer which also shuts the connection down when the returned signal is sent,
+    /// even if clones of the sender are still alive. Dropping the signal does not close it.
+    pub(crate) fn spawn_closable_writer(
+        self: &Arc<Self>,
+        writer: WriteHalf<TcpStream>,
+    ) -> (mpsc::UnboundedSender<Vec<u8>>, oneshot::Sender<()>) {
         let (tx, rx) = mpsc::unbounded_channel();
-        tokio::spawn(Transport::writer(rx, writer));
-        tx
+        let (close, closed) = oneshot::channel();
+        tokio::spawn(Transport::writer(rx, writer, closed));
+        (tx, close)
     }
 
-    /// Registers a request to receivers
+    /// Registers a request to receivers. The sender is dropped right away if the connection is
+    /// closed, so the call does not wait forever.
     pub fn add_receiver(
         self: Arc<Self>,
+        connection: u64,
         id: json_rpc::Id,
         sender: oneshot::Sender<json_rpc::Response>,
     ) {
-        log::debug!("Receiver length: {}", self.receivers.lock().unwrap().len());
-        self.receivers.lock().unwrap().insert(id, sender);
+        match self.connections.lock().unwrap().get_mut(&connection) {
+            Some(connection) => {
+                log::debug!("Receiver length: {}", connection.receivers.len());
+                connection.receivers.insert(id, sender);
+            }
+            None => warn!("Connection {} is closed. Dropping the call.", connection),
+        }
+    }
+
+    /// Removes the receiver of a call that does not wait for its response anymore.
+    pub fn remove_receiver(&self, connection: u64, id: &json_rpc::Id) {
+        if let Some(connection) = self.connections.lock().unwrap().get_mut(&connection) {
+            connection.receivers.remove(id);
+        }
+    }
+
+    /// Registers a handler of the incoming notifications of the method. The handler is removed
+    /// when it returns false.
+    pub fn add_notification_handler<F>(&self, method: String, handler: F)
+    where
+        F: Fn(&json_rpc::Request) -> bool + Send + 'static,
+    {
+        self.notification_handlers
+            .lock()
+            .unwrap()
+            .entry(method)
+            .or_default()
+            .push(Box::new(handler));
+    }
+
+    /// Registers the sender of the items of a subscription call. The items are sent to it once
+    /// the response of the call comes with the id of the subscription. The stream ends right
+    /// away if the connection is closed.
+    pub fn add_subscription(
+        &self,
+        connection: u64,
+        id: json_rpc::Id,
+        items: mpsc::UnboundedSender<Value>,
+    ) {
+        if let Some(connection) = self.connections.lock().unwrap().get_mut(&connection) {
+            connection.pending.insert(id, items);
+        }
+    }
+
+    /// Stops sending the items of the subscription.
+    pub fn remove_subscription(&self, connection: u64, id: u64) {
+        if let Some(connection) = self.connections.lock().unwrap().get_mut(&connection) {
+            connection.running.remove(&id);
+        }
+    }
+
+    /// Removes the sender of a subscription call that failed.
+    pub fn remove_pending_subscription(&self, connection: u64, request_id: &json_rpc::Id) {
+        if let Some(connection) = self.connections.lock().unwrap().get_mut(&connection) {
+            connection.pending.remove(request_id);
+        }
+    }
+
+    /// Sends the item of a subscription notification to its subscription.
+    pub(crate) fn handle_subscription(&self, connection: u64, notification: json_rpc::Request) {
+        let params: SubscriptionParams = match serde_json::from_value(notification.params) {
+            Ok(params) => params,
+            Err(e) => {
+                warn!("Invalid subscription notification: {}. Ignoring.", e);
+                return;
+            }
+        };
+
+        let mut connections = self.connections.lock().unwrap();
+        let running = match connections.get_mut(&connection) {
+            Some(connection) => &mut connection.running,
+            None => return,
+        };
+        if notification.method == SUBSCRIPTION_END {
+            // The stream of the subscription ends when its sender is dropped.
+            running.remove(&params.subscription);
+            return;
+        }
+        let item = params.result.unwrap_or(Value::Null);
+        match running.get(&params.subscription) {
+            Some(items) if items.send(item).is_ok() => {}
+            Some(_) => {
+                running.remove(&params.subscription);
+            }
+            None => warn!(
+                "Subscription {} is not found. Ignoring.",
+                params.subscription
+            ),
+        }
+    }
+
+    /// Passes the notification to the handlers of its method.
+    fn handle_notification(
+        handlers: &Mutex<NotificationHandlers>,
+        notification: json_rpc::Request,
+    ) {
+        // Handlers are called without holding the lock, so they can add handlers.
+        let method_handlers = handlers.lock().unwrap().remove(&notification.method);
+        let mut method_handlers = match method_handlers {
+            Some(method_handlers) => method_handlers,
+            None => {
+                warn!(
+                    "Notification '{}' does not have a handler. Ignoring.",
+                    notification.method
+                );
+                return;
+            }
+        };
+        method_handlers.retain(|handler| handler(&notification));
+
+        let mut handlers = handlers.lock().unwrap();
+        if let Some(added) = handlers.remove(&notification.method) {
+            method_handlers.extend(added);
+        }
+        if !method_handlers.is_empty() {
+            handlers.insert(notification.method, method_handlers);
+        }
+    }
+
+    /// Passes the response to the call waiting for it.
+    pub(crate) fn handle_response(&self, connection: u64, response: json_rpc::Response) {
+        let sender = {
+            let mut connections = self.connections.lock().unwrap();
+            connections.get_mut(&connection).and_then(|connection| {
+                // The subscription starts before the caller gets the response, so its first
+                // items are not missed.
+                if let Some(items) = connection.pending.remove(&response.id) {
+                    if let Some(id) = response.result.as_ref().and_then(Value::as_u64) {
+                        connection.running.insert(id, items);
+                    }
+                }
+                connection.receivers.remove(&response.id)
+            })
+        };
+        match sender {
+            // The call is not waiting anymore if the receiver is dropped.
+            Some(sender) => {
+                let _ = sender.send(response);
+            }
+            None => warn!("Response came with an unexpected identifier. Ignoring."),
+        }
+    }
+
+    /// Returns whether the notification is an item or the end of a subscription.
+    pub(crate) fn is_subscription(notification: &json_rpc::Request) -> bool {
+        notification.id.is_none()
+            && (notification.method == SUBSCRIPTION || notification.method == SUBSCRIPTION_END)
     }
 
     /// Reads incoming data in a loop and forwards the data to the corresponding receiver.
-    async fn reader(receivers: Arc<Mutex<Receivers>>, reader: ReadHalf<TcpStream>) {
-        let mut reader: Reader<json_rpc::Response, _> = Reader::new(reader);
+    /// Notifications are passed to their handlers.
+    async fn reader(
+        self: Arc<Self>,
+        connection: u64,
+        reader: ReadHalf<TcpStream>,
+        writer: mpsc::UnboundedSender<Vec<u8>>,
+    ) {
+        let mut reader: Reader<json_rpc::Message, _> = Reader::new(reader);
         loop {
             let next = reader.next().await;
             match next {
-                Some(Ok(codec::Type::Single(data))) => {
-                    let sender = {
-                        let mut receivers = receivers.lock().unwrap();
-                        receivers.remove(&data.id)
-                    };
-                    if let Some(sender) = sender {
-                        sender.send(data).unwrap();
+                Some(Ok(codec::Type::Single(json_rpc::Message::Request(request)))) => {
+                    if let Some(id) = request.id {
+                        // The client does not serve any methods, so the caller is not left
+                        // waiting for a response.
+                        warn!(
+                            "Client does not serve '{}'. Responding with an error.",
+                            request.method
+                        );
+                        let response = json_rpc::Response::from_error_data(
+                            json_rpc::ErrorKind::MethodNotFound,
+                            None,
+                            id,
+                        );
+                        let _ = writer.send(response.into());
+                    } else if Transport::is_subscription(&request) {
+                        self.handle_subscription(connection, request);
                     } else {
-                        warn!("Response came with an unexpected identifier. Ignoring.");
+                        Transport::handle_notification(&self.notification_handlers, request);
                     }
                 }
+                Some(Ok(codec::Type::Single(json_rpc::Message::Response(response)))) => {
+                    self.handle_response(connection, response);
+                }
                 Some(Ok(codec::Type::Batched(_))) => {
                     panic!("Client does not support batched requests yet.");
                 }
@@ -82,6 +311,8 @@ impl Transport {
                 }
             }
         }
+        // Nothing answers the calls of the connection anymore.
+        self.close_connection(connection);
     }
 
     async fn write_buf(
@@ -99,8 +330,31 @@ impl Transport {
     }
 
     /// Waits for incoming data from the receiver writes the incoming data to the connection.
-    async fn writer(mut receiver: mpsc::UnboundedReceiver<Vec<u8>>, mut writer: WriteHalf<TcpStream>) {
-        while let Some(data) = receiver.recv().await {
+    /// The connection is shut down when every sender is dropped or `closed` is signalled.
+    async fn writer(
+        mut receiver: mpsc::UnboundedReceiver<Vec<u8>>,
+        mut writer: WriteHalf<TcpStream>,
+        mut closed: oneshot::Receiver<()>,
+    ) {
+        let mut closable = true;
+        loop {
+            let data = if closable {
+                match future::select(Box::pin(receiver.recv()), &mut closed).await {
+                    Either::Left((data, _)) => data,
+                    Either::Right((Ok(()), _)) => break,
+                    // Nobody can close the connection anymore.
+                    Either::Right((Err(_), _)) => {
+                        closable = false;
+                        continue;
+                    }
+                }
+            } else {
+                receiver.recv().await
+            };
+            let data = match data {
+                Some(data) => data,
+                None => break,
+            };
             if let Err(e) =
                 Transport::write_buf(&mut writer, &(data.len() as u32).to_le_bytes()).await
             {
@@ -110,5 +364,8 @@ impl Transport {
                 log::error!("error occured during writing data {}", e);
             }
         }
+        if let Err(e) = writer.shutdown().await {
+            log::debug!("error occured during closing the connection {}", e);
+        }
     }
 }